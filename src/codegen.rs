@@ -0,0 +1,2928 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::errors::{codes, ShiftError};
+use crate::expr::{
+  Expr, FieldPatterns, FunctionDecl, LiteralValue, MatchArm, Method, Pattern, Property, Stmt, TraitMethod, VariantFields,
+};
+use crate::lint;
+use crate::token::{Span, Token, TokenType};
+
+// Tipos que o transpilador consegue inferir ou ler das anotações.
+// `Unknown` vem de parâmetros de closures sem anotação: o código é
+// emitido mesmo assim e o rustc faz a inferência.
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+  Int,
+  Float,
+  Str,
+  Bool,
+  Unit,
+  Array(Box<Type>),
+  Struct(String),
+  Enum(String),
+  // Valor de qualquer struct que implementa o trait
+  Trait(String),
+  // `int?`, que pode ser null. O `null` sozinho é `Option(Unknown)`.
+  Option(Box<Type>),
+  Function {
+    params: Vec<Type>,
+    ret: Box<Type>,
+  },
+  Unknown,
+}
+
+impl Type {
+  fn rust_name(&self) -> Option<String> {
+    match self {
+      Type::Int   => Some("i64".to_string()),
+      Type::Float => Some("f64".to_string()),
+      Type::Str   => Some("String".to_string()),
+      Type::Bool  => Some("bool".to_string()),
+      Type::Unit  => Some("()".to_string()),
+      Type::Array(inner) => Some(format!("Vec<{}>", inner.rust_name().unwrap_or_else(|| "_".to_string()))),
+      Type::Struct(name) | Type::Enum(name) => Some(name.clone()),
+      Type::Trait(name) => Some(format!("std::rc::Rc<dyn {}>", name)),
+      Type::Option(inner) => Some(format!("Option<{}>", inner.rust_name().unwrap_or_else(|| "_".to_string()))),
+      Type::Function { .. } | Type::Unknown => None,
+    }
+  }
+
+  // Unknown passa: quem confere é o rustc
+  fn is_numeric(&self) -> bool {
+    matches!(self, Type::Int | Type::Float | Type::Unknown)
+  }
+
+  // Tipos que não são Copy no Rust gerado
+  fn is_owned(&self) -> bool {
+    match self {
+      Type::Option(inner) => inner.is_owned(),
+      ty => matches!(ty, Type::Str | Type::Array(_) | Type::Struct(_) | Type::Enum(_) | Type::Trait(_)),
+    }
+  }
+
+  fn is_null(&self) -> bool {
+    matches!(self, Type::Option(inner) if **inner == Type::Unknown)
+  }
+
+  // `int?` vira `int`
+  fn without_null(&self) -> &Type {
+    match self {
+      Type::Option(inner) => inner,
+      ty => ty,
+    }
+  }
+}
+
+impl fmt::Display for Type {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Type::Int   => write!(f, "int"),
+      Type::Float => write!(f, "float"),
+      Type::Str   => write!(f, "string"),
+      Type::Bool  => write!(f, "bool"),
+      Type::Unit  => write!(f, "void"),
+      Type::Array(inner) => write!(f, "{}[]", inner),
+      Type::Option(inner) if **inner == Type::Unknown => write!(f, "null"),
+      Type::Option(inner) => write!(f, "{}?", inner),
+      Type::Struct(name) | Type::Enum(name) | Type::Trait(name) => write!(f, "{}", name),
+      Type::Function { .. } => write!(f, "function"),
+      Type::Unknown => write!(f, "unknown"),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+  ty: Type,
+  mutable: bool,
+}
+
+// Método de um `impl`. `receiver` é None para métodos sem `self`
+// e diz se o método precisa de `&mut self`.
+#[derive(Debug, Clone)]
+struct MethodInfo {
+  ty: Type,
+  receiver: Option<bool>,
+}
+
+// Os métodos de cada `impl Trait for` ficam separados dos do próprio
+// struct, que têm prioridade na chamada como no Rust
+#[derive(Debug, Clone, Default)]
+struct StructInfo {
+  fields: Vec<(String, Type)>,
+  methods: HashMap<String, MethodInfo>,
+  trait_methods: HashMap<String, Vec<(String, MethodInfo)>>,
+  traits: HashSet<String>,
+}
+
+impl StructInfo {
+  // `Err` com os nomes dos traits quando mais de um trait define o método
+  fn method(&self, name: &str) -> Result<Option<&MethodInfo>, Vec<String>> {
+    if let Some(method) = self.methods.get(name) {
+      return Ok(Some(method));
+    }
+
+    match self.trait_methods.get(name).map(Vec::as_slice) {
+      None | Some([]) => Ok(None),
+      Some([(_, method)]) => Ok(Some(method)),
+      Some(candidates) => Err(candidates.iter().map(|(trait_name, _)| trait_name.clone()).collect()),
+    }
+  }
+
+  fn trait_method(&self, trait_name: &str, name: &str) -> Option<&MethodInfo> {
+    self.trait_methods.get(name)?.iter().find(|(other, _)| other == trait_name).map(|(_, method)| method)
+  }
+}
+
+// Métodos na ordem da declaração; `defaults` são os que têm corpo
+#[derive(Debug, Clone, Default)]
+struct TraitInfo {
+  methods: Vec<(String, MethodInfo)>,
+  defaults: HashSet<String>,
+}
+
+impl TraitInfo {
+  fn method(&self, name: &str) -> Option<&MethodInfo> {
+    self.methods.iter().find(|(method, _)| method == name).map(|(_, info)| info)
+  }
+}
+
+#[derive(Debug, Clone)]
+enum VariantInfo {
+  Unit,
+  Tuple(Vec<Type>),
+  Named(Vec<(String, Type)>),
+}
+
+// `comparable` diz se o enum pode derivar PartialEq, para `==` funcionar
+#[derive(Debug, Clone, Default)]
+struct EnumInfo {
+  variants: Vec<(String, VariantInfo)>,
+  comparable: bool,
+}
+
+impl EnumInfo {
+  fn variant(&self, name: &str) -> Option<&VariantInfo> {
+    self.variants.iter().find(|(variant, _)| variant == name).map(|(_, info)| info)
+  }
+}
+
+// No interpretador arrays e structs são compartilhados entre variáveis,
+// no Rust gerado cada `let b = a` é uma cópia. As duas coisas só dão
+// resultados diferentes quando o valor muda no lugar, então o transpilador
+// recusa cópias de variáveis que mudam assim (`a[0] = 1`, `p.x = 2`,
+// `u.birthday()`). `deep` são as que mudam por dentro de um campo ou
+// item (`grid[0][1] = 2`), o que atinge valores guardados nelas.
+#[derive(Debug, Clone, Default)]
+struct Changes {
+  changed: HashSet<String>,
+  deep: HashSet<String>,
+}
+
+impl Changes {
+  fn of<'a>(statements: impl IntoIterator<Item = &'a Stmt>, mutating: &HashSet<String>) -> Self {
+    let mut changes = Self::default();
+
+    for statement in statements {
+      lint::walk(std::slice::from_ref(statement), &mut |node| {
+        let lint::Node::Expr(expr) = node else { return };
+        let target = match expr {
+          Expr::Set { object, .. } | Expr::SetIndex { object, .. } => Some((object.as_ref(), 1)),
+          Expr::CompoundAssign { target, .. } | Expr::Update { target, .. } => Some((target.as_ref(), 0)),
+          Expr::Call { callee, .. } => match callee.as_ref() {
+            Expr::Get { object, name, .. } if mutating.contains(&name.lexeme) => Some((object.as_ref(), 1)),
+            _ => None,
+          },
+          _ => None,
+        };
+
+        // `x += 1` troca o valor da variável, não muda nada no lugar
+        if let Some((target, extra)) = target
+          && let Some((root, depth)) = root_of(target)
+          && depth + extra > 0
+        {
+          changes.changed.insert(root.to_string());
+          if depth + extra > 1 {
+            changes.deep.insert(root.to_string());
+          }
+        }
+      });
+    }
+
+    changes
+  }
+}
+
+// Transpila a AST para um `main.rs`. Funções de topo viram itens `fn`,
+// o resto do programa vai para dentro de `fn main()`.
+pub struct CodeGenerator {
+  output: String,
+  indent: usize,
+  // Funções de topo, visíveis em qualquer lugar como no Rust
+  items: HashMap<String, Type>,
+  // Structs de topo, com campos e métodos
+  structs: HashMap<String, StructInfo>,
+  enums: HashMap<String, EnumInfo>,
+  traits: HashMap<String, TraitInfo>,
+  scopes: Vec<HashMap<String, Binding>>,
+  return_type: Option<Type>,
+  // Se precisa emitir as funções `at`/`at_mut` de índices
+  uses_index: bool,
+  // Se precisa emitir as funções de aritmética inteira checada
+  uses_arithmetic: bool,
+  // Métodos que pedem `&mut self`, e se cada função ou método pode
+  // devolver ou guardar os argumentos (ver `keeps_arguments`)
+  mutating: HashSet<String>,
+  keeps: HashMap<String, bool>,
+  // O que muda no lugar no corpo atual, se o valor sendo gerado guardou
+  // uma cópia de array ou struct e se essa cópia é só emprestada
+  changes: Changes,
+  copied: bool,
+  lending: bool,
+  // Se o último elo gerado de uma cadeia com `?.` podia ser null por si
+  // só, e não só porque a cadeia parou antes
+  nullable_link: bool,
+}
+
+pub fn generate(statements: &[Stmt]) -> Result<String, ShiftError> {
+  CodeGenerator::new().generate(statements)
+}
+
+impl CodeGenerator {
+  pub fn new() -> Self {
+    Self {
+      output: String::new(),
+      indent: 0,
+      items: HashMap::new(),
+      structs: HashMap::new(),
+      enums: HashMap::new(),
+      traits: HashMap::new(),
+      scopes: vec![HashMap::new()],
+      return_type: None,
+      uses_index: false,
+      uses_arithmetic: false,
+      mutating: HashSet::new(),
+      keeps: HashMap::new(),
+      changes: Changes::default(),
+      copied: false,
+      lending: false,
+      nullable_link: false,
+    }
+  }
+
+  pub fn generate(mut self, statements: &[Stmt]) -> Result<String, ShiftError> {
+    self.output.push_str("// Gerado pelo shift, não edite à mão.\n");
+    self.output.push_str("#![allow(unused_mut, unused_parens, unused_variables, dead_code)]\n\n");
+
+    // Primeiro as assinaturas, para que a ordem de declaração não importe
+    self.register_types(statements)?;
+    for statement in statements {
+      if let Stmt::Function { declaration } = statement {
+        let name = self.function_name(declaration);
+        let ty = self.signature(declaration)?;
+        self.add_keeps(&name, declaration);
+        self.items.insert(name, ty);
+      }
+    }
+
+    for statement in statements {
+      match statement {
+        Stmt::Struct { name, .. } => self.struct_item(name),
+        Stmt::Enum { name, .. } => self.enum_item(name),
+        _ => continue,
+      }
+      self.output.push('\n');
+    }
+
+    for statement in statements {
+      if let Stmt::Trait { name, methods } = statement {
+        self.trait_item(name, methods)?;
+        self.output.push('\n');
+      }
+    }
+
+    for statement in statements {
+      if let Stmt::Impl { name, trait_name, methods } = statement {
+        self.impl_item(name, trait_name.as_ref(), methods)?;
+        self.output.push('\n');
+      }
+    }
+
+    for statement in statements {
+      if let Stmt::Function { declaration } = statement {
+        self.function_item(declaration)?;
+        self.output.push('\n');
+      }
+    }
+
+    let main: Vec<&Stmt> = statements.iter().filter(|statement| !matches!(
+      statement,
+      Stmt::Function { .. } | Stmt::Struct { .. } | Stmt::Impl { .. } | Stmt::Enum { .. } | Stmt::Trait { .. }
+    )).collect();
+    self.changes = Changes::of(main.iter().copied(), &self.mutating);
+
+    self.line("fn main() {");
+    self.indent += 1;
+    for statement in main {
+      self.statement(statement)?;
+    }
+    self.indent -= 1;
+    self.line("}");
+
+    if self.uses_index {
+      self.output.push('\n');
+      self.output.push_str(INDEX_HELPERS);
+    }
+    if self.uses_arithmetic {
+      self.output.push('\n');
+      self.output.push_str(ARITHMETIC_HELPERS);
+    }
+
+    Ok(self.output)
+  }
+
+  fn line(&mut self, text: &str) {
+    for _ in 0..self.indent {
+      self.output.push_str("    ");
+    }
+    self.output.push_str(text);
+    self.output.push('\n');
+  }
+
+  fn define(&mut self, name: &str, ty: Type, mutable: bool) {
+    if let Some(scope) = self.scopes.last_mut() {
+      scope.insert(name.to_string(), Binding { ty, mutable });
+    }
+  }
+
+  fn lookup(&self, name: &str) -> Option<Binding> {
+    for scope in self.scopes.iter().rev() {
+      if let Some(binding) = scope.get(name) {
+        return Some(binding.clone());
+      }
+    }
+
+    self.items.get(name).map(|ty| Binding { ty: ty.clone(), mutable: false })
+  }
+
+  fn annotation(&self, token: &Token) -> Result<Type, ShiftError> {
+    self.type_named(&token.lexeme, token)
+  }
+
+  fn type_named(&self, name: &str, token: &Token) -> Result<Type, ShiftError> {
+    if let Some(inner) = name.strip_suffix("[]") {
+      return Ok(Type::Array(Box::new(self.type_named(inner, token)?)));
+    }
+    if let Some(inner) = name.strip_suffix('?') {
+      return Ok(Type::Option(Box::new(self.type_named(inner, token)?)));
+    }
+
+    match name {
+      "int"    => Ok(Type::Int),
+      "float"  => Ok(Type::Float),
+      "string" => Ok(Type::Str),
+      "bool"   => Ok(Type::Bool),
+      "void"   => Ok(Type::Unit),
+      other if self.structs.contains_key(other) => Ok(Type::Struct(other.to_string())),
+      other if self.enums.contains_key(other) => Ok(Type::Enum(other.to_string())),
+      other if self.traits.contains_key(other) => Ok(Type::Trait(other.to_string())),
+      other => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        token.span,
+        format!("Unknown type '{}'.", other),
+      )),
+    }
+  }
+
+  // ---------- Structs e enums ----------
+
+  // Nomes antes dos campos, para que um tipo possa usar outro declarado depois
+  fn register_types(&mut self, statements: &[Stmt]) -> Result<(), ShiftError> {
+    for statement in statements {
+      match statement {
+        Stmt::Struct { name, .. } => { self.structs.insert(name.lexeme.clone(), StructInfo::default()); },
+        Stmt::Enum { name, .. } => { self.enums.insert(name.lexeme.clone(), EnumInfo::default()); },
+        Stmt::Trait { name, .. } => { self.traits.insert(name.lexeme.clone(), TraitInfo::default()); },
+        _ => {},
+      }
+    }
+
+    for statement in statements {
+      if let Stmt::Struct { name, fields } = statement {
+        let typed = self.typed_fields(&name.lexeme, fields)?;
+
+        if let Some(info) = self.structs.get_mut(&name.lexeme) {
+          info.fields = typed;
+        }
+      }
+
+      if let Stmt::Enum { name, variants } = statement {
+        let mut infos = Vec::new();
+        for variant in variants {
+          let info = match &variant.fields {
+            VariantFields::Unit => VariantInfo::Unit,
+            VariantFields::Tuple(types) => {
+              VariantInfo::Tuple(types.iter().map(|annotation| self.annotation(annotation)).collect::<Result<_, _>>()?)
+            },
+            VariantFields::Named(fields) => {
+              VariantInfo::Named(self.typed_fields(&format!("{}.{}", name.lexeme, variant.name.lexeme), fields)?)
+            },
+          };
+          infos.push((variant.name.lexeme.clone(), info));
+        }
+
+        if let Some(info) = self.enums.get_mut(&name.lexeme) {
+          info.variants = infos;
+        }
+      }
+    }
+    self.mark_comparable_enums();
+
+    let impls: Vec<_> = statements.iter().filter_map(|statement| match statement {
+      Stmt::Impl { name, trait_name, methods } => Some((name, trait_name.as_ref(), methods)),
+      _ => None,
+    }).collect();
+    let traits: Vec<_> = statements.iter().filter_map(|statement| match statement {
+      Stmt::Trait { name, methods } => Some((name, methods)),
+      _ => None,
+    }).collect();
+
+    let defaults = traits.iter().flat_map(|(_, methods)| methods.iter().map(|trait_method| &trait_method.method));
+    let mutating = mutating_methods(impls.iter().flat_map(|(_, _, methods)| methods.iter()).chain(defaults.clone()));
+    for method in impls.iter().flat_map(|(_, _, methods)| methods.iter()).chain(defaults) {
+      self.add_keeps(&self.function_name(&method.declaration), &method.declaration);
+    }
+    self.mutating = mutating.clone();
+
+    for (name, methods) in traits {
+      let mut infos = Vec::new();
+      for trait_method in methods {
+        let method = &trait_method.method;
+        let method_name = self.function_name(&method.declaration);
+        if !method.has_self {
+          let span = method.declaration.name.as_ref().map(|name| name.span).unwrap_or_default();
+          return Err(ShiftError::codegen(
+            codes::UNSUPPORTED,
+            span,
+            format!("Trait method '{}' needs 'self' to be transpiled.", method_name),
+          ));
+        }
+
+        let ty = self.signature(&method.declaration)?;
+        infos.push((method_name.clone(), MethodInfo { ty, receiver: Some(mutating.contains(&method_name)) }));
+      }
+
+      let defaults = methods.iter()
+        .filter(|trait_method| trait_method.has_default)
+        .map(|trait_method| self.function_name(&trait_method.method.declaration))
+        .collect();
+
+      if let Some(info) = self.traits.get_mut(&name.lexeme) {
+        info.methods = infos;
+        info.defaults = defaults;
+      }
+    }
+
+    for (name, trait_name, methods) in impls {
+      if !self.structs.contains_key(&name.lexeme) {
+        return Err(ShiftError::codegen(
+          codes::UNDEFINED_VARIABLE,
+          name.span,
+          format!("Cannot implement methods for '{}': no struct with that name.", name.lexeme),
+        ));
+      }
+
+      let mut infos = Vec::new();
+      for method in methods {
+        let method_name = self.function_name(&method.declaration);
+        let ty = self.signature(&method.declaration)?;
+        let receiver = method.has_self.then(|| mutating.contains(&method_name));
+        infos.push((method_name, MethodInfo { ty, receiver }));
+      }
+
+      if let Some(trait_name) = trait_name {
+        infos = self.trait_methods(name, trait_name, methods, infos)?;
+      }
+
+      if let Some(info) = self.structs.get_mut(&name.lexeme) {
+        match trait_name {
+          Some(trait_name) => {
+            for (method_name, method) in infos {
+              info.trait_methods.entry(method_name).or_default().push((trait_name.lexeme.clone(), method));
+            }
+            info.traits.insert(trait_name.lexeme.clone());
+          },
+          None => info.methods.extend(infos),
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  // Confere o `impl Trait for Nome`: o Rust exige as mesmas assinaturas do
+  // trait. Devolve os métodos do bloco mais os padrão que ele não redefiniu.
+  fn trait_methods(
+    &self,
+    name: &Token,
+    trait_name: &Token,
+    methods: &[Method],
+    mut infos: Vec<(String, MethodInfo)>,
+  ) -> Result<Vec<(String, MethodInfo)>, ShiftError> {
+    let Some(trait_info) = self.traits.get(&trait_name.lexeme) else {
+      return Err(ShiftError::codegen(
+        codes::UNDEFINED_VARIABLE,
+        trait_name.span,
+        format!("Undefined trait '{}'.", trait_name.lexeme),
+      ));
+    };
+
+    for (method, (method_name, info)) in methods.iter().zip(&infos) {
+      let span = method.declaration.name.as_ref().map(|name| name.span).unwrap_or_default();
+      let Some(expected) = trait_info.method(method_name) else {
+        return Err(ShiftError::codegen(
+          codes::TRAIT_MISMATCH,
+          span,
+          format!("Method '{}' is not a member of trait '{}'.", method_name, trait_name.lexeme),
+        ));
+      };
+
+      if info.receiver.is_none() || info.ty != expected.ty {
+        return Err(ShiftError::codegen(
+          codes::TRAIT_MISMATCH,
+          span,
+          format!("Method '{}' must match its declaration in trait '{}'.", method_name, trait_name.lexeme),
+        ).with_help(format!("the trait declares it as '{}'", signature_text(method_name, &expected.ty))));
+      }
+    }
+
+    for (method_name, expected) in &trait_info.methods {
+      if infos.iter().any(|(name, _)| name == method_name) {
+        continue;
+      }
+
+      if !trait_info.defaults.contains(method_name) {
+        return Err(ShiftError::codegen(
+          codes::TRAIT_MISMATCH,
+          name.span,
+          format!("Missing method '{}' of trait '{}' in impl for '{}'.", method_name, trait_name.lexeme, name.lexeme),
+        ).with_label(trait_name.span, "required by this trait"));
+      }
+      infos.push((method_name.clone(), expected.clone()));
+    }
+
+    Ok(infos)
+  }
+
+  fn typed_fields(&self, owner: &str, fields: &[crate::expr::Field]) -> Result<Vec<(String, Type)>, ShiftError> {
+    let mut typed = Vec::new();
+
+    for field in fields {
+      let ty = match &field.type_annotation {
+        Some(annotation) => self.annotation(annotation)?,
+        None => return Err(ShiftError::codegen(
+          codes::MISSING_ANNOTATION,
+          field.name.span,
+          format!("Field '{}' of '{}' needs a type annotation to be transpiled.", field.name.lexeme, owner),
+        ).with_help(format!("annotate it like '{}: int'", field.name.lexeme))),
+      };
+      typed.push((field.name.lexeme.clone(), ty));
+    }
+
+    Ok(typed)
+  }
+
+  // Structs não derivam PartialEq (no interpretador `==` compara identidade),
+  // então um enum só é comparável se nada dentro dele for struct ou função
+  fn mark_comparable_enums(&mut self) {
+    for info in self.enums.values_mut() {
+      info.comparable = true;
+    }
+
+    loop {
+      let mut changed = false;
+
+      for name in self.enums.keys().cloned().collect::<Vec<_>>() {
+        let info = &self.enums[&name];
+        let comparable = info.comparable && info.variants.iter().all(|(_, variant)| match variant {
+          VariantInfo::Unit => true,
+          VariantInfo::Tuple(types) => types.iter().all(|ty| self.is_comparable(ty)),
+          VariantInfo::Named(fields) => fields.iter().all(|(_, ty)| self.is_comparable(ty)),
+        });
+
+        if comparable != info.comparable {
+          self.enums.get_mut(&name).expect("enum registrado").comparable = comparable;
+          changed = true;
+        }
+      }
+
+      if !changed {
+        return;
+      }
+    }
+  }
+
+  fn is_comparable(&self, ty: &Type) -> bool {
+    match ty {
+      Type::Array(inner) => self.is_comparable(inner),
+      Type::Enum(name) => self.enums.get(name).is_some_and(|info| info.comparable),
+      Type::Struct(_) | Type::Trait(_) | Type::Function { .. } | Type::Unknown => false,
+      _ => true,
+    }
+  }
+
+  fn enum_item(&mut self, name: &Token) {
+    let info = self.enums[&name.lexeme].clone();
+    let rust = |ty: &Type| ty.rust_name().unwrap_or_else(|| "_".to_string());
+
+    self.line(if info.comparable { "#[derive(Debug, Clone, PartialEq)]" } else { "#[derive(Debug, Clone)]" });
+    self.line(&format!("enum {} {{", name.lexeme));
+    self.indent += 1;
+    for (variant, fields) in &info.variants {
+      match fields {
+        VariantInfo::Unit => self.line(&format!("{},", variant)),
+        VariantInfo::Tuple(types) => {
+          let types: Vec<String> = types.iter().map(rust).collect();
+          self.line(&format!("{}({}),", variant, types.join(", ")));
+        },
+        VariantInfo::Named(fields) => {
+          let fields: Vec<String> = fields.iter().map(|(field, ty)| format!("{}: {}", field, rust(ty))).collect();
+          self.line(&format!("{} {{ {} }},", variant, fields.join(", ")));
+        },
+      }
+    }
+    self.indent -= 1;
+    self.line("}");
+  }
+
+  fn struct_item(&mut self, name: &Token) {
+    let fields = self.structs[&name.lexeme].fields.clone();
+
+    self.line("#[derive(Debug, Clone)]");
+    self.line(&format!("struct {} {{", name.lexeme));
+    self.indent += 1;
+    for (field, ty) in &fields {
+      self.line(&format!("{}: {},", field, ty.rust_name().unwrap_or_else(|| "_".to_string())));
+    }
+    self.indent -= 1;
+    self.line("}");
+  }
+
+  // Todo struct deriva Debug, então o trait pode exigir Debug e um
+  // `Rc<dyn Trait>` continua podendo ser mostrado no print
+  fn trait_item(&mut self, name: &Token, methods: &[TraitMethod]) -> Result<(), ShiftError> {
+    self.line(&format!("trait {}: std::fmt::Debug {{", name.lexeme));
+    self.indent += 1;
+
+    for (i, trait_method) in methods.iter().enumerate() {
+      if i > 0 && (trait_method.has_default || methods[i - 1].has_default) {
+        self.output.push('\n');
+      }
+
+      let method = &trait_method.method;
+      let method_name = self.function_name(&method.declaration);
+      let info = self.traits[&name.lexeme].method(&method_name).cloned().expect("registrado em register_types");
+      let mutable = info.receiver == Some(true);
+      let self_code = if mutable { "&mut self" } else { "&self" };
+
+      if trait_method.has_default {
+        let binding = Binding { ty: Type::Trait(name.lexeme.clone()), mutable };
+        self.function_code(&method.declaration, &info.ty, HashMap::new(), Some((self_code, binding)))?;
+        continue;
+      }
+
+      // Sem corpo o Rust não aceita `mut` nos parâmetros
+      let Type::Function { params, ret } = &info.ty else { unreachable!() };
+      let mut param_list = vec![self_code.to_string()];
+      for (param, ty) in method.declaration.params.iter().zip(params) {
+        param_list.push(format!("{}: {}", param.name.lexeme, ty.rust_name().unwrap_or_else(|| "_".to_string())));
+      }
+      self.line(&format!("{};", function_header(&method_name, &param_list, ret)));
+    }
+
+    self.indent -= 1;
+    self.line("}");
+    Ok(())
+  }
+
+  fn impl_item(&mut self, name: &Token, trait_name: Option<&Token>, methods: &[Method]) -> Result<(), ShiftError> {
+    match trait_name {
+      Some(trait_name) => self.line(&format!("impl {} for {} {{", trait_name.lexeme, name.lexeme)),
+      None => self.line(&format!("impl {} {{", name.lexeme)),
+    }
+    self.indent += 1;
+
+    for (i, method) in methods.iter().enumerate() {
+      if i > 0 {
+        self.output.push('\n');
+      }
+
+      let method_name = self.function_name(&method.declaration);
+      let struct_info = &self.structs[&name.lexeme];
+      let info = match trait_name {
+        Some(trait_name) => struct_info.trait_method(&trait_name.lexeme, &method_name),
+        None => struct_info.methods.get(&method_name),
+      }.cloned().expect("registrado em register_types");
+      let receiver = info.receiver.map(|mutable| {
+        let code = if mutable { "&mut self" } else { "&self" };
+        (code, Binding { ty: Type::Struct(name.lexeme.clone()), mutable })
+      });
+
+      self.function_code(&method.declaration, &info.ty, HashMap::new(), receiver)?;
+    }
+
+    self.indent -= 1;
+    self.line("}");
+    Ok(())
+  }
+
+  // ---------- Funções ----------
+
+  fn function_name(&self, declaration: &FunctionDecl) -> String {
+    declaration.name.as_ref().map_or(String::new(), |name| name.lexeme.clone())
+  }
+
+  fn signature(&self, declaration: &FunctionDecl) -> Result<Type, ShiftError> {
+    let name = declaration.name.as_ref().map_or("closure", |name| name.lexeme.as_str());
+    let mut params = Vec::new();
+
+    for param in &declaration.params {
+      match &param.type_annotation {
+        Some(annotation) => params.push(self.annotation(annotation)?),
+        None => return Err(ShiftError::codegen(
+          codes::MISSING_ANNOTATION,
+          param.name.span,
+          format!("Parameter '{}' of function '{}' needs a type annotation to be transpiled.", param.name.lexeme, name),
+        ).with_help(format!("annotate it like '{}: int'", param.name.lexeme))),
+      }
+    }
+
+    let ret = match &declaration.return_type {
+      Some(annotation) => self.annotation(annotation)?,
+      None if returns_value(&declaration.body) => {
+        let span = declaration.name.as_ref().map(|name| name.span).unwrap_or_default();
+        return Err(ShiftError::codegen(
+          codes::MISSING_ANNOTATION,
+          span,
+          format!("Function '{}' returns a value and needs a '-> type' annotation to be transpiled.", name),
+        ).with_help("declare the return type after the parameters, like 'fn name(x: int) -> int'"));
+      },
+      None => Type::Unit,
+    };
+
+    Ok(Type::Function { params, ret: Box::new(ret) })
+  }
+
+  // Itens `fn` do Rust não capturam variáveis, então o corpo só enxerga
+  // os parâmetros, a própria função e as funções de topo.
+  fn function_item(&mut self, declaration: &FunctionDecl) -> Result<(), ShiftError> {
+    let name = self.function_name(declaration);
+    let signature = self.signature(declaration)?;
+
+    let mut own_scope = HashMap::new();
+    own_scope.insert(name.clone(), Binding { ty: signature.clone(), mutable: false });
+    self.add_keeps(&name, declaration);
+
+    self.function_code(declaration, &signature, own_scope, None)?;
+    self.define(&name, signature, false);
+    Ok(())
+  }
+
+  // `receiver` é o `&self` ou `&mut self` dos métodos, com o binding de `self`
+  fn function_code(
+    &mut self,
+    declaration: &FunctionDecl,
+    signature: &Type,
+    own_scope: HashMap<String, Binding>,
+    receiver: Option<(&str, Binding)>,
+  ) -> Result<(), ShiftError> {
+    let name = self.function_name(declaration);
+    let Type::Function { params, ret } = signature else { unreachable!() };
+
+    let changes = Changes::of(&declaration.body, &self.mutating);
+    let mut param_scope = HashMap::new();
+    let mut param_list = Vec::new();
+    if let Some((code, binding)) = receiver {
+      param_scope.insert("self".to_string(), binding);
+      param_list.push(code.to_string());
+    }
+    for (param, ty) in declaration.params.iter().zip(params) {
+      self.check_param(&changes, &param.name, ty)?;
+      param_scope.insert(param.name.lexeme.clone(), Binding { ty: ty.clone(), mutable: true });
+      param_list.push(format!("mut {}: {}", param.name.lexeme, ty.rust_name().unwrap_or_else(|| "_".to_string())));
+    }
+
+    let header = format!("{} {{", function_header(&name, &param_list, ret));
+
+    let scopes = std::mem::replace(&mut self.scopes, vec![own_scope, param_scope]);
+    let return_type = self.return_type.replace(ret.as_ref().clone());
+    let changes = std::mem::replace(&mut self.changes, changes);
+
+    self.line(&header);
+    self.indent += 1;
+    let result = declaration.body.iter().try_for_each(|statement| self.statement(statement));
+    self.indent -= 1;
+    self.line("}");
+
+    self.scopes = scopes;
+    self.return_type = return_type;
+    self.changes = changes;
+    result
+  }
+
+  // Nomes repetidos entre structs contam como um só
+  fn add_keeps(&mut self, name: &str, declaration: &FunctionDecl) {
+    let keeps = keeps_arguments(declaration);
+    *self.keeps.entry(name.to_string()).or_default() |= keeps;
+  }
+
+  // O Rust passa uma cópia, então mudar o parâmetro no lugar não
+  // chegaria em quem chamou
+  fn check_param(&self, changes: &Changes, name: &Token, ty: &Type) -> Result<(), ShiftError> {
+    if !is_shared(ty) || !changes.changed.contains(&name.lexeme) {
+      return Ok(());
+    }
+
+    Err(ShiftError::codegen(
+      codes::UNSUPPORTED,
+      name.span,
+      format!("Parameter '{}' is changed in place, but in Rust the function gets a copy and the caller would not see the change.", name.lexeme),
+    ).with_help(format!("{}; return the new value instead", SHARED_HELP)))
+  }
+
+  // ---------- Instruções ----------
+
+  fn block(&mut self, statements: &[Stmt]) -> Result<(), ShiftError> {
+    self.indent += 1;
+    self.scopes.push(HashMap::new());
+
+    let result = statements.iter().try_for_each(|statement| self.statement(statement));
+
+    self.scopes.pop();
+    self.indent -= 1;
+    result
+  }
+
+  // Corpos de if/while/for são sempre `Stmt::Block`
+  fn body(&mut self, stmt: &Stmt) -> Result<(), ShiftError> {
+    match stmt {
+      Stmt::Block { statements } => self.block(statements),
+      other => self.block(std::slice::from_ref(other)),
+    }
+  }
+
+  fn statement(&mut self, stmt: &Stmt) -> Result<(), ShiftError> {
+    match stmt {
+      Stmt::Expression { expression } => {
+        let (code, _) = self.expression(expression)?;
+        self.line(&format!("{};", code));
+        Ok(())
+      },
+
+      Stmt::Let { name, type_annotation, initializer } => {
+        let declared = type_annotation.as_ref().map(|annotation| self.annotation(annotation)).transpose()?;
+
+        match initializer {
+          Some(initializer) => {
+            let (code, ty) = self.binding_value(name, declared, initializer)?;
+            self.line(&declaration_line("let mut", &name.lexeme, &ty, Some(&code)));
+            self.define(&name.lexeme, ty, true);
+          },
+          None => {
+            let ty = declared.unwrap_or(Type::Unknown);
+            self.line(&declaration_line("let mut", &name.lexeme, &ty, None));
+            self.define(&name.lexeme, ty, true);
+          },
+        }
+
+        Ok(())
+      },
+
+      Stmt::Const { name, type_annotation, initializer } => {
+        let declared = type_annotation.as_ref().map(|annotation| self.annotation(annotation)).transpose()?;
+        let (code, ty) = self.binding_value(name, declared, initializer)?;
+
+        self.line(&declaration_line("let", &name.lexeme, &ty, Some(&code)));
+        self.define(&name.lexeme, ty, false);
+        Ok(())
+      },
+
+      Stmt::Block { statements } => {
+        self.line("{");
+        self.block(statements)?;
+        self.line("}");
+        Ok(())
+      },
+
+      Stmt::If { condition, then_branch, else_branch } => {
+        let condition = self.condition(condition)?;
+        self.line(&format!("if {} {{", condition));
+        self.body(then_branch)?;
+
+        let mut else_branch = else_branch.as_deref();
+        while let Some(branch) = else_branch {
+          match branch {
+            Stmt::If { condition, then_branch, else_branch: next } => {
+              let condition = self.condition(condition)?;
+              self.line(&format!("}} else if {} {{", condition));
+              self.body(then_branch)?;
+              else_branch = next.as_deref();
+            },
+            other => {
+              self.line("} else {");
+              self.body(other)?;
+              else_branch = None;
+            },
+          }
+        }
+
+        self.line("}");
+        Ok(())
+      },
+
+      Stmt::While { condition, body } => {
+        let condition = self.condition(condition)?;
+        self.line(&format!("while {} {{", condition));
+        self.body(body)?;
+        self.line("}");
+        Ok(())
+      },
+
+      Stmt::Loop { body } => {
+        self.line("loop {");
+        self.body(body)?;
+        self.line("}");
+        Ok(())
+      },
+
+      Stmt::For { variable, start, end, body } => {
+        let (start, start_ty) = self.expression(start)?;
+        let (end, end_ty) = self.expression(end)?;
+
+        for ty in [&start_ty, &end_ty] {
+          if !matches!(ty, Type::Int | Type::Unknown) {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              variable.span,
+              "Range bounds must be integers.",
+            ));
+          }
+        }
+
+        self.line(&format!("for {} in {}..{} {{", variable.lexeme, start, end));
+        self.scopes.push(HashMap::new());
+        self.define(&variable.lexeme, Type::Int, false);
+        let result = self.body(body);
+        self.scopes.pop();
+        result?;
+        self.line("}");
+        Ok(())
+      },
+
+      Stmt::Break => {
+        self.line("break;");
+        Ok(())
+      },
+
+      Stmt::Continue => {
+        self.line("continue;");
+        Ok(())
+      },
+
+      Stmt::Function { declaration } => self.function_item(declaration),
+
+      Stmt::Return { value } => {
+        match value {
+          Some(value) => {
+            // Variáveis locais deixam de existir, só `self` continua visível
+            let expected = self.return_type.clone().unwrap_or(Type::Unknown);
+            let local = !matches!(root_of(value), Some(("self", _)));
+            let lending = std::mem::replace(&mut self.lending, local);
+            let (code, ty) = self.coerced(value, &expected)?;
+            self.lending = lending;
+            let code = match (&self.return_type, ty) {
+              (Some(Type::Float), Type::Int) => format!("({}) as f64", code),
+              _ => code,
+            };
+            self.line(&format!("return {};", code));
+          },
+          None => self.line("return;"),
+        }
+
+        Ok(())
+      },
+
+      Stmt::Match { subject, arms } => self.match_statement(subject, arms),
+
+      // Viram itens do Rust, que o `generate` emite antes do main
+      Stmt::Struct { name, .. } | Stmt::Impl { name, .. } | Stmt::Enum { name, .. } | Stmt::Trait { name, .. } => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        name.span,
+        format!("'{}' must be declared at the top level to be transpiled.", name.lexeme),
+      )),
+    }
+  }
+
+  // Valor de um let/const, convertendo int para float quando a anotação pede
+  fn binding_value(&mut self, name: &Token, declared: Option<Type>, initializer: &Expr) -> Result<(String, Type), ShiftError> {
+    let outer = std::mem::replace(&mut self.copied, false);
+    let (code, ty) = match &declared {
+      Some(declared) => self.coerced(initializer, declared)?,
+      None => self.owned(initializer)?,
+    };
+    let copied = std::mem::replace(&mut self.copied, outer);
+    self.check_copy_target(&name.lexeme, name.span, initializer, copied, false)?;
+
+    if declared.is_none() && ty.is_null() {
+      return Err(ShiftError::codegen(
+        codes::MISSING_ANNOTATION,
+        name.span,
+        format!("'{}' starts as null and needs a type annotation to be transpiled.", name.lexeme),
+      ).with_help(format!("annotate it like '{}: int?'", name.lexeme)));
+    }
+
+    match (declared, ty) {
+      (None, ty) => Ok((code, ty)),
+      (Some(Type::Float), Type::Int) => Ok((format!("({}) as f64", code), Type::Float)),
+      (Some(declared), Type::Unknown) => Ok((code, declared)),
+      // `let xs: int[] = []`
+      (Some(declared @ Type::Array(_)), Type::Array(inner)) if *inner == Type::Unknown => Ok((code, declared)),
+      (Some(declared), ty) if declared == ty => Ok((code, ty)),
+      (Some(declared), ty) => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        name.span,
+        format!("Cannot initialize '{}' of type {} with a value of type {}.", name.lexeme, declared, ty),
+      )),
+    }
+  }
+
+  fn condition(&mut self, condition: &Expr) -> Result<String, ShiftError> {
+    let (code, ty) = self.expression(condition)?;
+
+    match ty {
+      Type::Bool | Type::Unknown => Ok(code),
+      ty => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        condition.span(),
+        format!("Conditions must be bool to be transpiled, found {} in '{}'.", ty, condition),
+      )),
+    }
+  }
+
+  // ---------- Expressões ----------
+
+  // Variáveis String são clonadas quando o valor muda de dono,
+  // para que o Rust gerado não sofra com `use of moved value`.
+  fn owned(&mut self, expr: &Expr) -> Result<(String, Type), ShiftError> {
+    let (code, ty) = self.expression(expr)?;
+
+    // Arrays e structs também são clonados: no Rust gerado `let b = a` copia
+    // o valor, enquanto no interpretador as duas variáveis apontam para o mesmo
+    // (o `share` recusa os casos em que isso muda o resultado). Campos
+    // também, já que não dá para mover para fora de `&self`.
+    let is_variant = matches!(expr, Expr::Get { object, .. } if self.enum_name(object).is_some());
+    if !is_variant && matches!(expr, Expr::Variable { .. } | Expr::Get { optional: false, .. } | Expr::Index { .. }) {
+      self.share(expr, &ty)?;
+    }
+    if ty.is_owned() && !is_variant && matches!(expr, Expr::Variable { .. } | Expr::Get { optional: false, .. }) {
+      return Ok((format!("{}.clone()", code), ty));
+    }
+
+    Ok((code, ty))
+  }
+
+  // `expr` vai ser copiado para outro lugar. Se ele muda no lugar em algum
+  // ponto do corpo, a cópia deixaria de acompanhar a mudança. Cópias só
+  // emprestadas para uma função que não as guarda não têm esse problema.
+  fn share(&mut self, expr: &Expr, ty: &Type) -> Result<(), ShiftError> {
+    if !is_shared(ty) {
+      return Ok(());
+    }
+    self.copied = true;
+
+    match root_of(expr) {
+      Some((root, _)) if !self.lending && self.changes.changed.contains(root) => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        expr.span(),
+        format!("Cannot copy '{}' here because it is changed in place; the copy in Rust would not follow the change.", root),
+      ).with_help(SHARED_HELP)),
+      _ => Ok(()),
+    }
+  }
+
+  // `target` recebe um valor que guardou uma cópia. Se `target` muda no
+  // lugar, no interpretador a mudança chegaria também no original.
+  // `inside` é para quando o valor vai para um campo ou item de `target`.
+  fn check_copy_target(&self, target: &str, span: Span, value: &Expr, copied: bool, inside: bool) -> Result<(), ShiftError> {
+    let literal = matches!(value, Expr::Array { .. } | Expr::StructLiteral { .. });
+    let changes = if inside || literal { &self.changes.deep } else { &self.changes.changed };
+    if !copied || !changes.contains(target) {
+      return Ok(());
+    }
+
+    Err(ShiftError::codegen(
+      codes::UNSUPPORTED,
+      span,
+      format!("'{}' is changed in place but holds a copy in Rust; in Shift the change would also reach the value it came from.", target),
+    ).with_help(SHARED_HELP))
+  }
+
+  // Valor indo para um lugar do tipo `expected`. Onde um trait é esperado
+  // o struct vira `Rc<dyn Trait>`, inclusive dentro de listas literais.
+  fn coerced(&mut self, expr: &Expr, expected: &Type) -> Result<(String, Type), ShiftError> {
+    if let (Expr::Array { elements, .. }, Type::Array(inner)) = (expr, expected) {
+      return self.array(elements, inner);
+    }
+
+    // Onde cabe `int?` um int vira `Some(..)` e o null vira `None`
+    if let Type::Option(inner) = expected {
+      let (code, ty) = self.coerced(expr, inner)?;
+      return Ok(match ty {
+        ty if ty.is_null() => (code, expected.clone()),
+        Type::Option(_) | Type::Unknown => (code, ty),
+        Type::Int if **inner == Type::Float => (format!("Some(({}) as f64)", code), expected.clone()),
+        ty => (format!("Some({})", code), Type::Option(Box::new(ty))),
+      });
+    }
+
+    let (code, ty) = self.owned(expr)?;
+    match (expected, &ty) {
+      (Type::Trait(trait_name), Type::Struct(struct_name)) => {
+        if !self.structs[struct_name].traits.contains(trait_name) {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            expr.span(),
+            format!("'{}' does not implement trait '{}'.", struct_name, trait_name),
+          ).with_help(format!("add 'impl {} for {} {{ ... }}'", trait_name, struct_name)));
+        }
+        Ok((format!("std::rc::Rc::new({})", code), expected.clone()))
+      },
+      _ => Ok((code, ty)),
+    }
+  }
+
+  // Valor gravado num campo ou item de `object`
+  fn stored(&mut self, object: &Expr, value: &Expr, expected: &Type) -> Result<(String, Type), ShiftError> {
+    let outer = std::mem::replace(&mut self.copied, false);
+    let result = self.coerced(value, expected)?;
+    let copied = std::mem::replace(&mut self.copied, outer);
+
+    if let Some((root, _)) = root_of(object) {
+      self.check_copy_target(root, value.span(), value, copied, true)?;
+    }
+    Ok(result)
+  }
+
+  fn expression(&mut self, expr: &Expr) -> Result<(String, Type), ShiftError> {
+    match expr {
+      Expr::Literal { value, .. } => match value {
+        // Sem sufixo o rustc pode inferir i32, que não comporta o valor
+        LiteralValue::Integer(i) if i32::try_from(*i).is_err() => Ok((format!("{}i64", i), Type::Int)),
+        LiteralValue::Integer(i) => Ok((i.to_string(), Type::Int)),
+        LiteralValue::Float(f) => Ok((format!("{:?}", f), Type::Float)),
+        LiteralValue::String(s) => Ok((format!("String::from({:?})", s), Type::Str)),
+        LiteralValue::Boolean(b) => Ok((b.to_string(), Type::Bool)),
+        LiteralValue::Null => Ok(("None".to_string(), Type::Option(Box::new(Type::Unknown)))),
+      },
+
+      Expr::Grouping { expression, .. } => {
+        let (code, ty) = self.expression(expression)?;
+        Ok((format!("({})", code), ty))
+      },
+
+      Expr::Unary { operator, right, .. } => {
+        let (code, ty) = self.expression(right)?;
+
+        match (&operator.token_type, &ty) {
+          // Só `-i64::MIN` estoura, então um literal pode ir direto
+          (TokenType::Minus, Type::Int) if code.parse::<i64>().is_err() => {
+            self.uses_arithmetic = true;
+            Ok((format!("checked(i64::checked_neg({}), \"-\")", code), ty))
+          },
+          (TokenType::Minus, Type::Int | Type::Float | Type::Unknown) => Ok((format!("-{}", code), ty)),
+          (TokenType::Bang, Type::Bool | Type::Unknown) => Ok((format!("!{}", code), Type::Bool)),
+          (TokenType::BitNot, Type::Int | Type::Unknown) => Ok((format!("!{}", code), Type::Int)),
+          _ => Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            format!("Operator '{}' cannot be applied to {}.", operator.lexeme, ty),
+          )),
+        }
+      },
+
+      Expr::Binary { left, operator, right, .. } => {
+        if operator.token_type == TokenType::Plus && self.is_string(expr)? {
+          let (format, args) = self.format_parts(expr)?;
+          return Ok((format_call("format!", &format, &args), Type::Str));
+        }
+
+        let (l, lt) = self.expression(left)?;
+        let (r, rt) = self.expression(right)?;
+        self.binary(operator, l, lt, r, rt)
+      },
+
+      // O lado direito vira uma closure, então só roda quando o esquerdo é None
+      Expr::Logical { left, operator, right, .. } if operator.token_type == TokenType::QuestionQuestion => {
+        let (l, lt) = self.owned(left)?;
+        let inner = match &lt {
+          ty if ty.is_null() => return self.owned(right),
+          Type::Option(inner) => inner.as_ref().clone(),
+          ty => return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            left.span(),
+            format!("Left side of '??' is {}, which is never null in Rust.", ty),
+          ).with_help("declare it as nullable, like 'let x: int? = null', or remove the '??'")),
+        };
+
+        let (r, rt) = self.coerced(right, &inner)?;
+        match rt {
+          Type::Option(_) => Ok((format!("{}.or_else(|| {})", receiver(l), r), lt)),
+          Type::Int if inner == Type::Float => Ok((format!("{}.unwrap_or_else(|| ({}) as f64)", receiver(l), r), inner)),
+          rt if rt == inner || rt == Type::Unknown => Ok((format!("{}.unwrap_or_else(|| {})", receiver(l), r), inner)),
+          rt => Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            format!("Operator '??' needs a {} on the right side, found {}.", inner, rt),
+          )),
+        }
+      },
+
+      Expr::Logical { left, operator, right, .. } => {
+        let (l, lt) = self.expression(left)?;
+        let (r, rt) = self.expression(right)?;
+
+        for ty in [&lt, &rt] {
+          if !matches!(ty, Type::Bool | Type::Unknown) {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              operator.span,
+              format!("Operator '{}' needs bool operands to be transpiled.", operator.lexeme),
+            ));
+          }
+        }
+
+        Ok((format!("{} {} {}", l, operator.lexeme, r), Type::Bool))
+      },
+
+      Expr::Ternary { condition, then_branch, else_branch, .. } => {
+        let (c, ct) = self.expression(condition)?;
+        if !matches!(ct, Type::Bool | Type::Unknown) {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            condition.span(),
+            format!("Condition must be a bool to be transpiled, found {}.", ct),
+          ));
+        }
+
+        let (t, tt) = self.owned(then_branch)?;
+        let (e, et) = self.owned(else_branch)?;
+        let (t, e, ty) = match (&tt, &et) {
+          (Type::Int, Type::Float) => (format!("({}) as f64", t), e, Type::Float),
+          (Type::Float, Type::Int) => (t, format!("({}) as f64", e), Type::Float),
+          _ if tt == et || et == Type::Unknown => (t, e, tt),
+          _ if tt == Type::Unknown => (t, e, et),
+          // `ok ? 1 : null` é um `int?`
+          (Type::Option(_), Type::Option(_)) if tt.is_null() => (t, e, et),
+          (Type::Option(_), Type::Option(_)) if et.is_null() => (t, e, tt),
+          (Type::Option(inner), _) if tt.is_null() || **inner == et => (t, format!("Some({})", e), Type::Option(Box::new(et))),
+          (_, Type::Option(inner)) if et.is_null() || **inner == tt => (format!("Some({})", t), e, Type::Option(Box::new(tt))),
+          _ => return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            expr.span(),
+            format!("Both branches must have the same type, found {} and {}.", tt, et),
+          )),
+        };
+
+        Ok((format!("if {} {{ {} }} else {{ {} }}", c, t, e), ty))
+      },
+
+      Expr::Get { object, name, optional, .. } => {
+        if *optional || in_optional_chain(object) {
+          return self.optional_field(object, name, *optional);
+        }
+
+        if let Some(enum_name) = self.enum_name(object) {
+          return self.unit_variant(&enum_name, name);
+        }
+
+        let (code, ty) = self.expression(object)?;
+        let field = self.field(&ty, object, name)?;
+        Ok((format!("{}.{}", receiver(code), name.lexeme), field))
+      },
+
+      Expr::Set { object, name, value, .. } => {
+        let (code, ty) = self.place(object)?;
+        self.check_mutable(object)?;
+        let field = self.field(&ty, object, name)?;
+        let (value_code, value_ty) = self.stored(object, value, &field)?;
+
+        let value_code = match (&field, &value_ty) {
+          (Type::Float, Type::Int) => format!("({}) as f64", value_code),
+          _ if field == value_ty || value_ty == Type::Unknown => value_code,
+          (Type::Array(_), Type::Array(inner)) if **inner == Type::Unknown => value_code,
+          _ => return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            value.span(),
+            format!("Cannot store a {} in field '{}' of type {}.", value_ty, name.lexeme, field),
+          )),
+        };
+
+        Ok((format!("{}.{} = {}", receiver(code), name.lexeme, value_code), Type::Unit))
+      },
+
+      Expr::StructLiteral { name, variant: None, properties, span } => self.struct_literal(name, properties, *span),
+
+      Expr::StructLiteral { name, variant: Some(variant), properties, span } => self.variant_literal(name, variant, properties, *span),
+
+      Expr::Match { subject, arms, .. } => self.match_expression(subject, arms),
+
+      Expr::Variable { name, .. } => {
+        match self.lookup(&name.lexeme) {
+          Some(binding) => Ok((name.lexeme.clone(), binding.ty)),
+          None => Err(ShiftError::codegen(
+            codes::UNDEFINED_VARIABLE,
+            name.span,
+            format!("Undefined variable '{}' (Rust functions cannot capture variables from outside).", name.lexeme),
+          )),
+        }
+      },
+
+      Expr::Assign { name, value, .. } => {
+        let binding = self.lookup(&name.lexeme).ok_or_else(|| {
+          ShiftError::codegen(
+            codes::UNDEFINED_VARIABLE,
+            name.span,
+            format!("Undefined variable '{}'.", name.lexeme),
+          )
+        })?;
+
+        if !binding.mutable {
+          return Err(ShiftError::codegen(
+            codes::CONSTANT_ASSIGNMENT,
+            name.span,
+            format!("Cannot assign to '{}'.", name.lexeme),
+          ));
+        }
+
+        let outer = std::mem::replace(&mut self.copied, false);
+        let (code, ty) = self.coerced(value, &binding.ty)?;
+        let copied = std::mem::replace(&mut self.copied, outer);
+        self.check_copy_target(&name.lexeme, name.span, value, copied, false)?;
+
+        let code = match (&binding.ty, ty) {
+          (Type::Float, Type::Int) => format!("({}) as f64", code),
+          _ => code,
+        };
+
+        Ok((format!("{} = {}", name.lexeme, code), Type::Unit))
+      },
+
+      Expr::Array { elements, .. } => self.array(elements, &Type::Unknown),
+
+      Expr::Object { span, .. } => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        *span,
+        "Objects cannot be transpiled to Rust yet.",
+      ).with_help("declare a struct for it")),
+
+      Expr::Spread { span, .. } => Err(ShiftError::codegen(codes::UNSUPPORTED, *span, "'...' is only allowed inside array literals.")),
+
+      Expr::Index { object, index, .. } if in_optional_chain(object) => self.optional_index(object, index),
+
+      Expr::Index { object, index, .. } => {
+        let (object_code, element) = self.indexed(object, false)?;
+        let index = self.index(index)?;
+        self.uses_index = true;
+
+        Ok((format!("at(&{}, {}).clone()", object_code, index), element))
+      },
+
+      Expr::SetIndex { object, index, value, .. } => {
+        let (object_code, element) = self.indexed(object, true)?;
+        self.check_mutable(object)?;
+        let index = self.index(index)?;
+        let (value_code, value_ty) = self.stored(object, value, &element)?;
+        self.uses_index = true;
+
+        let value_code = match (&element, &value_ty) {
+          (Type::Float, Type::Int) => format!("({}) as f64", value_code),
+          _ if element == value_ty || element == Type::Unknown || value_ty == Type::Unknown => value_code,
+          _ => return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            value.span(),
+            format!("Cannot store a {} in an array of {}.", value_ty, element),
+          )),
+        };
+
+        Ok((format!("*at_mut(&mut {}, {}) = {}", object_code, index, value_code), Type::Unit))
+      },
+
+      Expr::CompoundAssign { target, operator, value, .. } => {
+        let (code, ty) = self.place(target)?;
+        self.check_mutable(target)?;
+
+        let (value_code, value_ty) = self.owned(value)?;
+        let binary = operator.compound_operator().expect("o parser só cria CompoundAssign com +=, -=, *= e /=");
+
+        // Valida os tipos como se fosse `x + valor`, mas o resultado tem que caber em x
+        let is_concat = binary.token_type == TokenType::Plus && ty == Type::Str && value_ty == Type::Str;
+        if !is_concat {
+          let (_, result) = self.binary(&binary, code.clone(), ty.clone(), value_code.clone(), value_ty.clone())?;
+          if result != ty && ty != Type::Unknown {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              operator.span,
+              format!("Operator '{}' would turn {} '{}' into {}.", operator.lexeme, ty, code, result),
+            ));
+          }
+        }
+
+        // Inteiros passam pelas funções checadas; o valor vem antes do
+        // lugar, como no `+=` do Rust
+        if ty == Type::Int && value_ty == Type::Int {
+          let (operation, _) = self.binary(&binary, "l".to_string(), Type::Int, "r".to_string(), Type::Int)?;
+          return Ok((format!("apply({}, &mut {}, |l, r| {})", value_code, code, operation), Type::Unit));
+        }
+
+        let value_code = match (&ty, &value_ty) {
+          (Type::Float, Type::Int) => format!("({}) as f64", value_code),
+          (Type::Str, _) => format!("&{}", value_code),
+          _ => value_code,
+        };
+
+        Ok((format!("{} {} {}", code, operator.lexeme, value_code), Type::Unit))
+      },
+
+      // O Rust não tem ++, então vira um bloco que atualiza e devolve o valor
+      Expr::Update { target, operator, prefix, .. } => {
+        let (code, ty) = self.place(target)?;
+
+        self.check_mutable(target)?;
+
+        let one = match ty {
+          Type::Int => "1",
+          Type::Float => "1.0",
+          _ => return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            format!("Operator '{}' cannot be applied to {}.", operator.lexeme, ty),
+          )),
+        };
+        let update = match (&ty, &operator.token_type) {
+          (Type::Int, TokenType::Increment) => format!("*slot = checked(i64::checked_add(*slot, 1), \"{}\");", operator.lexeme),
+          (Type::Int, _) => format!("*slot = checked(i64::checked_sub(*slot, 1), \"{}\");", operator.lexeme),
+          (_, TokenType::Increment) => format!("*slot += {};", one),
+          _ => format!("*slot -= {};", one),
+        };
+        if ty == Type::Int {
+          self.uses_arithmetic = true;
+        }
+
+        if *prefix {
+          Ok((format!("{{ let slot = &mut {}; {} *slot }}", code, update), ty))
+        } else {
+          Ok((format!("{{ let slot = &mut {}; let old = *slot; {} old }}", code, update), ty))
+        }
+      },
+
+      Expr::Call { callee, paren, arguments, .. } => {
+        if let Expr::Variable { name, .. } = callee.as_ref()
+          && self.lookup(&name.lexeme).is_none()
+        {
+          return self.builtin_call(name, arguments);
+        }
+
+        if let Expr::Get { object, name, optional, .. } = callee.as_ref() {
+          if *optional || in_optional_chain(object) {
+            return self.optional_method_call(object, name, paren, arguments, *optional);
+          }
+          return self.method_call(object, name, paren, arguments);
+        }
+
+        let keeps = match callee.as_ref() {
+          Expr::Variable { name, .. } => self.keeps.get(&name.lexeme).copied().unwrap_or(true),
+          _ => true,
+        };
+        let (callee, callee_ty) = self.expression(callee)?;
+        let params = match &callee_ty {
+          Type::Function { params, .. } => params.clone(),
+          _ => Vec::new(),
+        };
+
+        let args = self.arguments(arguments, &params, keeps, false)?;
+
+        let ret = match callee_ty {
+          Type::Function { params, ret } => {
+            if params.len() != args.len() {
+              return Err(ShiftError::codegen(
+                codes::ARITY_MISMATCH,
+                paren.span,
+                format!("Expected {} arguments but got {}.", params.len(), args.len()),
+              ));
+            }
+            *ret
+          },
+          Type::Unknown => Type::Unknown,
+          _ => return Err(ShiftError::codegen(
+            codes::NOT_CALLABLE,
+            paren.span,
+            "Can only call functions.",
+          )),
+        };
+
+        Ok((format!("{}({})", callee, args.join(", ")), ret))
+      },
+
+      Expr::Template { .. } => {
+        let (format, args) = self.format_parts(expr)?;
+        Ok((format_call("format!", &format, &args), Type::Str))
+      },
+
+      Expr::Closure { declaration, .. } => self.closure(declaration),
+    }
+  }
+
+  fn binary(&mut self, operator: &Token, l: String, lt: Type, r: String, rt: Type) -> Result<(String, Type), ShiftError> {
+    let op = &operator.lexeme;
+
+    // Int misturado com float é promovido, como no interpretador
+    let (l, r, operand) = match (&lt, &rt) {
+      (Type::Int, Type::Float) => (format!("({} as f64)", l), r, Type::Float),
+      (Type::Float, Type::Int) => (l, format!("({} as f64)", r), Type::Float),
+      (Type::Unknown, _) => (l, r, rt.clone()),
+      _ => (l, r, lt.clone()),
+    };
+
+    match operator.token_type {
+      TokenType::Plus | TokenType::Minus | TokenType::Star | TokenType::Slash | TokenType::Percent => {
+        if !lt.is_numeric() || !rt.is_numeric() {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            format!("Operator '{}' requires numbers.", op),
+          ));
+        }
+        if operand != Type::Int {
+          return Ok((format!("{} {} {}", l, op, r), operand));
+        }
+
+        // Como no interpretador, estouro e divisão por zero são erros
+        self.uses_arithmetic = true;
+        let code = match operator.token_type {
+          TokenType::Plus => format!("checked(i64::checked_add({}, {}), \"{}\")", l, r, op),
+          TokenType::Minus => format!("checked(i64::checked_sub({}, {}), \"{}\")", l, r, op),
+          TokenType::Star => format!("checked(i64::checked_mul({}, {}), \"{}\")", l, r, op),
+          TokenType::Slash => format!("checked(i64::checked_div({}, divisor({}, \"Division by zero.\")), \"{}\")", l, r, op),
+          _ => format!("checked(i64::checked_rem({}, divisor({}, \"Modulo by zero.\")), \"{}\")", l, r, op),
+        };
+        Ok((code, Type::Int))
+      },
+
+      // Como no interpretador, o resultado é sempre float
+      TokenType::StarStar => match operand {
+        Type::Int => Ok((format!("f64::powf(({}) as f64, ({}) as f64)", l, r), Type::Float)),
+        Type::Float => Ok((format!("f64::powf({}, {})", l, r), Type::Float)),
+        _ => Err(ShiftError::codegen(codes::TYPE_ERROR, operator.span, "Operator '**' requires numbers.")),
+      },
+
+      TokenType::BitAnd | TokenType::OrBitwise | TokenType::BitXor | TokenType::ShiftLeft | TokenType::ShiftRight => {
+        if !matches!(lt, Type::Int | Type::Unknown) || !matches!(rt, Type::Int | Type::Unknown) {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            format!("Operator '{}' requires integers.", op),
+          ));
+        }
+        Ok((format!("{} {} {}", l, op, r), Type::Int))
+      },
+
+      TokenType::WrappingPlus | TokenType::WrappingMinus | TokenType::WrappingStar
+      | TokenType::SaturatingPlus | TokenType::SaturatingMinus | TokenType::SaturatingStar => {
+        if !matches!(lt, Type::Int | Type::Unknown) || !matches!(rt, Type::Int | Type::Unknown) {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            format!("Operator '{}' requires integers.", op),
+          ));
+        }
+
+        let method = match operator.token_type {
+          TokenType::WrappingPlus => "wrapping_add",
+          TokenType::WrappingMinus => "wrapping_sub",
+          TokenType::WrappingStar => "wrapping_mul",
+          TokenType::SaturatingPlus => "saturating_add",
+          TokenType::SaturatingMinus => "saturating_sub",
+          _ => "saturating_mul",
+        };
+        Ok((format!("i64::{}({}, {})", method, l, r), Type::Int))
+      },
+
+      TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
+        if !lt.is_numeric() || !rt.is_numeric() {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            operator.span,
+            "Comparison operators require numbers.",
+          ));
+        }
+        Ok((format!("{} {} {}", l, op, r), Type::Bool))
+      },
+
+      TokenType::EqualEqual | TokenType::BangEqual => {
+        // `x == null` vira `x.is_none()`
+        if lt.is_null() || rt.is_null() {
+          let (value, ty) = if lt.is_null() { (r, &rt) } else { (l, &lt) };
+          if !matches!(ty, Type::Option(_)) {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              operator.span,
+              format!("Only nullable values can be compared with null in Rust, found {}.", ty),
+            ));
+          }
+
+          let check = if operator.token_type == TokenType::EqualEqual { "is_none" } else { "is_some" };
+          return Ok((format!("{}.{}()", receiver(value), check), Type::Bool));
+        }
+
+        // Um `int?` comparado com um int compara com `Some(..)`
+        let (l, r) = match (&lt, &rt) {
+          (Type::Option(inner), other) if **inner == *other => (l, format!("Some({})", r)),
+          (other, Type::Option(inner)) if **inner == *other => (format!("Some({})", l), r),
+          _ => (l, r),
+        };
+        let (lt, rt) = (lt.without_null(), rt.without_null());
+
+        // No interpretador structs comparam identidade, o Rust não tem isso
+        if matches!(lt, Type::Struct(_) | Type::Trait(_)) || matches!(rt, Type::Struct(_) | Type::Trait(_)) {
+          return Err(ShiftError::codegen(
+            codes::UNSUPPORTED,
+            operator.span,
+            format!("Structs cannot be compared with '{}' in Rust.", op),
+          ));
+        }
+        if [&lt, &rt].iter().any(|ty| matches!(ty, Type::Enum(_)) && !self.is_comparable(ty)) {
+          return Err(ShiftError::codegen(
+            codes::UNSUPPORTED,
+            operator.span,
+            format!("Enums holding structs or functions cannot be compared with '{}' in Rust.", op),
+          ).with_help("use 'match' to check the variant"));
+        }
+        Ok((format!("{} {} {}", l, op, r), Type::Bool))
+      },
+
+      _ => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        operator.span,
+        format!("Operator '{}' cannot be transpiled.", op),
+      )),
+    }
+  }
+
+  // Gravar em `lista[i]` exige que a variável da lista seja mutável
+  fn check_mutable(&self, target: &Expr) -> Result<(), ShiftError> {
+    match target {
+      Expr::Variable { name, .. } if self.lookup(&name.lexeme).is_some_and(|binding| !binding.mutable) => {
+        Err(ShiftError::codegen(
+          codes::CONSTANT_ASSIGNMENT,
+          name.span,
+          format!("Cannot assign to '{}'.", name.lexeme),
+        ))
+      },
+      Expr::Index { object, .. } | Expr::Get { object, .. } => self.check_mutable(object),
+      _ => Ok(()),
+    }
+  }
+
+  // Alvo de `+=` e `++` como expressão de lugar do Rust
+  fn place(&mut self, target: &Expr) -> Result<(String, Type), ShiftError> {
+    match target {
+      Expr::Index { object, index, .. } => {
+        let (object_code, element) = self.indexed(object, true)?;
+        let index = self.index(index)?;
+        self.uses_index = true;
+        Ok((format!("*at_mut(&mut {}, {})", object_code, index), element))
+      },
+      Expr::Get { object, name, .. } => {
+        let (code, ty) = self.place(object)?;
+        let field = self.field(&ty, object, name)?;
+        Ok((format!("{}.{}", receiver(code), name.lexeme), field))
+      },
+      target => self.expression(target),
+    }
+  }
+
+  // Tipo do campo `name` de um valor do tipo `ty`
+  fn field(&self, ty: &Type, object: &Expr, name: &Token) -> Result<Type, ShiftError> {
+    let Type::Struct(struct_name) = ty else {
+      return Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        object.span(),
+        format!("Only structs have fields to be transpiled, found {} in '{}'.", ty, object),
+      ));
+    };
+
+    let info = &self.structs[struct_name];
+    match info.fields.iter().find(|(field, _)| *field == name.lexeme) {
+      Some((_, ty)) => Ok(ty.clone()),
+      None if info.methods.contains_key(&name.lexeme) || info.trait_methods.contains_key(&name.lexeme) => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        name.span,
+        format!("Method '{}' must be called to be transpiled.", name.lexeme),
+      )),
+      None => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        name.span,
+        format!("'{}' has no field '{}'.", struct_name, name.lexeme),
+      )),
+    }
+  }
+
+  // Tipo dentro do `Option` de quem usa `?.`
+  fn nullable(&self, ty: Type, object: &Expr) -> Result<Type, ShiftError> {
+    match ty {
+      Type::Option(inner) if *inner != Type::Unknown => Ok(*inner),
+      ty => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        object.span(),
+        format!("'?.' needs a nullable value to be transpiled, found {} in '{}'.", ty, object),
+      ).with_help("declare it as nullable, like 'let user: User? = null', or use '.'")),
+    }
+  }
+
+  // Um `.` depois de um `?.` continua dentro da cadeia, que já é um
+  // Option. Se o elo anterior podia ser null por si só, o interpretador
+  // daria erro onde o Rust daria None, então pede o `?.`.
+  fn chain_link(&self, ty: Type, object: &Expr, link: &str) -> Result<Type, ShiftError> {
+    match ty {
+      Type::Option(inner) if !self.nullable_link => Ok(*inner),
+      _ => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        object.span(),
+        format!("The value before '{}' can be null by itself, which Rust cannot tell apart from a '?.' that stopped.", link),
+      ).with_help("use '?.' here too")),
+    }
+  }
+
+  // `user?.name` vira `user.as_ref().map(|value| value.name.clone())`, e
+  // `user?.address.city` ganha mais um `map` no fim
+  fn optional_field(&mut self, object: &Expr, name: &Token, optional: bool) -> Result<(String, Type), ShiftError> {
+    let (code, ty) = self.expression(object)?;
+    let inner = if optional { self.nullable(ty, object)? } else { self.chain_link(ty, object, &format!(".{}", name.lexeme))? };
+    let field = self.field(&inner, object, name)?;
+
+    self.nullable_link = matches!(field, Type::Option(_));
+    let (combinator, ty) = match field {
+      field @ Type::Option(_) => ("and_then", field),
+      field => ("map", Type::Option(Box::new(field))),
+    };
+    Ok((format!("{}.as_ref().{}(|value| value.{}.clone())", receiver(code), combinator, name.lexeme), ty))
+  }
+
+  // `user?.tags[0]` indexa dentro do `map`
+  fn optional_index(&mut self, object: &Expr, index: &Expr) -> Result<(String, Type), ShiftError> {
+    let (code, ty) = self.expression(object)?;
+    let element = match self.chain_link(ty, object, "[")? {
+      Type::Array(element) => *element,
+      ty => return Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        object.span(),
+        format!("Only arrays can be indexed in Rust, found {}.", ty),
+      )),
+    };
+
+    let value = self.unused_name();
+    let index = self.index(index)?;
+    self.uses_index = true;
+
+    self.nullable_link = matches!(element, Type::Option(_));
+    let (combinator, ty) = match element {
+      element @ Type::Option(_) => ("and_then", element),
+      element => ("map", Type::Option(Box::new(element))),
+    };
+    Ok((format!("{}.as_ref().{}(|{}| at({}, {}).clone())", receiver(code), combinator, value, value, index), ty))
+  }
+
+  // Nome para o valor do `Some` que não esconde nenhuma variável
+  fn unused_name(&self) -> String {
+    let mut value = "value".to_string();
+    while self.lookup(&value).is_some() {
+      value.push('_');
+    }
+    value
+  }
+
+  // `user?.greet()` chama o método dentro de um `map`
+  fn optional_method_call(&mut self, object: &Expr, name: &Token, paren: &Token, arguments: &[Expr], optional: bool) -> Result<(String, Type), ShiftError> {
+    let (code, ty) = self.expression(object)?;
+    let inner = if optional { self.nullable(ty, object)? } else { self.chain_link(ty, object, &format!(".{}", name.lexeme))? };
+
+    // No meio da cadeia o valor é uma cópia, mudá-la não muda o original
+    if !optional && self.mutating.contains(&name.lexeme) {
+      return Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        name.span,
+        format!("Method '{}' changes 'self' and cannot be called after '?.' in Rust.", name.lexeme),
+      ));
+    }
+
+    let value = self.unused_name();
+
+    let variable = Expr::Variable { name: Token::new(TokenType::Identifier(value.clone()), value.clone(), object.span()), span: object.span() };
+    self.scopes.push(HashMap::from([(value.clone(), Binding { ty: inner, mutable: true })]));
+    let result = self.method_call(&variable, name, paren, arguments);
+    self.scopes.pop();
+    let (call, ret) = result?;
+
+    let (code, access) = if self.mutating.contains(&name.lexeme) {
+      self.check_mutable(object)?;
+      (self.place(object)?.0, "as_mut")
+    } else {
+      (code, "as_ref")
+    };
+    self.nullable_link = matches!(ret, Type::Option(_));
+    let (combinator, ty) = match ret {
+      ret @ Type::Option(_) => ("and_then", ret),
+      ret => ("map", Type::Option(Box::new(ret))),
+    };
+
+    Ok((format!("{}.{}().{}(|{}| {})", receiver(code), access, combinator, value, call), ty))
+  }
+
+  // `User.new(..)` vira `User::new(..)` e `u.greet()` continua igual
+  fn method_call(&mut self, object: &Expr, name: &Token, paren: &Token, arguments: &[Expr]) -> Result<(String, Type), ShiftError> {
+    if let Some(enum_name) = self.enum_name(object) {
+      return self.tuple_variant(&enum_name, name, paren, arguments);
+    }
+
+    let is_static = matches!(object, Expr::Variable { name: struct_name, .. }
+      if self.structs.contains_key(&struct_name.lexeme) && self.lookup(&struct_name.lexeme).is_none());
+
+    let (prefix, owner) = if is_static {
+      let Expr::Variable { name: struct_name, .. } = object else { unreachable!() };
+      (format!("{}::", struct_name.lexeme), Type::Struct(struct_name.lexeme.clone()))
+    } else {
+      let (_, ty) = self.expression(object)?;
+      if !matches!(ty, Type::Struct(_) | Type::Trait(_)) {
+        return Err(ShiftError::codegen(
+          codes::TYPE_ERROR,
+          object.span(),
+          format!("Only structs have methods to be transpiled, found {} in '{}'.", ty, object),
+        ));
+      }
+      (String::new(), ty)
+    };
+
+    let method = match &owner {
+      Type::Struct(struct_name) => match self.structs[struct_name].method(&name.lexeme) {
+        Ok(method) => method.cloned(),
+        Err(traits) => {
+          let traits: Vec<String> = traits.iter().map(|trait_name| format!("'{}'", trait_name)).collect();
+          return Err(ShiftError::codegen(
+            codes::TRAIT_MISMATCH,
+            name.span,
+            format!("Method '{}' of '{}' is ambiguous, it comes from traits {}.", name.lexeme, struct_name, traits.join(" and ")),
+          ).with_help(format!("rename the method in one of the traits or add 'fn {}' to 'impl {}'", name.lexeme, struct_name)));
+        },
+      },
+      Type::Trait(trait_name) => self.traits[trait_name].method(&name.lexeme).cloned(),
+      _ => unreachable!(),
+    };
+    let Some(method) = method else {
+      return Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        name.span,
+        format!("'{}' has no method '{}'.", owner, name.lexeme),
+      ));
+    };
+
+    // `Rc<dyn Trait>` só empresta o valor para leitura
+    let through_trait = matches!(owner, Type::Trait(_)) && !matches!(object, Expr::Variable { name, .. } if name.lexeme == "self");
+    if through_trait && method.receiver == Some(true) {
+      return Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        name.span,
+        format!("Method '{}' changes 'self' and cannot be called through trait '{}' in Rust.", name.lexeme, owner),
+      ).with_help("call it on the struct value instead"));
+    }
+
+    let prefix = match (is_static, method.receiver) {
+      (true, None) => prefix,
+      (false, Some(mutable)) => {
+        let (code, _) = if mutable { self.place(object)? } else { self.expression(object)? };
+        if mutable {
+          self.check_mutable(object)?;
+        }
+        format!("{}.", receiver(code))
+      },
+      (true, Some(_)) => return Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        name.span,
+        format!("Method '{}' takes 'self' and must be called on a '{}' value.", name.lexeme, owner),
+      )),
+      (false, None) => return Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        name.span,
+        format!("'{}' has no 'self', call it as '{}.{}()'.", name.lexeme, owner, name.lexeme),
+      )),
+    };
+
+    let Type::Function { params, ret } = method.ty else { unreachable!() };
+    if params.len() != arguments.len() {
+      return Err(ShiftError::codegen(
+        codes::ARITY_MISMATCH,
+        paren.span,
+        format!("Expected {} arguments but got {}.", params.len(), arguments.len()),
+      ));
+    }
+
+    let keeps = self.keeps.get(&name.lexeme).copied().unwrap_or(true);
+    let args = self.arguments(arguments, &params, keeps, !is_static)?;
+
+    Ok((format!("{}{}({})", prefix, name.lexeme, args.join(", ")), *ret))
+  }
+
+  // Argumentos de uma chamada. Se a função pode guardar o que recebe, o
+  // resultado conta como cópia dos argumentos (e do `self` em `on_value`).
+  fn arguments(&mut self, arguments: &[Expr], params: &[Type], keeps: bool, on_value: bool) -> Result<Vec<String>, ShiftError> {
+    let outer = std::mem::replace(&mut self.copied, false);
+    let lending = std::mem::replace(&mut self.lending, !keeps);
+
+    let mut args = Vec::with_capacity(arguments.len());
+    for (i, argument) in arguments.iter().enumerate() {
+      args.push(self.coerced(argument, params.get(i).unwrap_or(&Type::Unknown))?.0);
+    }
+
+    self.lending = lending;
+    let copied = std::mem::replace(&mut self.copied, outer);
+    self.copied |= keeps && (copied || on_value);
+    Ok(args)
+  }
+
+  fn struct_literal(&mut self, name: &Token, properties: &[Property], span: Span) -> Result<(String, Type), ShiftError> {
+    let Some(info) = self.structs.get(&name.lexeme).cloned() else {
+      return Err(ShiftError::codegen(
+        codes::UNDEFINED_VARIABLE,
+        name.span,
+        format!("Undefined struct '{}'.", name.lexeme),
+      ));
+    };
+
+    let ty = Type::Struct(name.lexeme.clone());
+    let fields = self.literal_fields(&name.lexeme, &info.fields, properties, Some(&ty), span)?;
+
+    if fields.is_empty() {
+      return Ok((name.lexeme.clone(), ty));
+    }
+    Ok((format!("{} {{ {} }}", name.lexeme, fields.join(", ")), ty))
+  }
+
+  fn variant_literal(&mut self, name: &Token, variant: &Token, properties: &[Property], span: Span) -> Result<(String, Type), ShiftError> {
+    let info = self.enum_info(name)?;
+    let fields = match info.variant(&variant.lexeme) {
+      Some(VariantInfo::Named(fields)) => fields.clone(),
+      Some(_) => return Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        variant.span,
+        format!("'{}.{}' has no named fields.", name.lexeme, variant.lexeme),
+      )),
+      None => return Err(undefined_variant(name, variant)),
+    };
+
+    let type_name = format!("{}.{}", name.lexeme, variant.lexeme);
+    let fields = self.literal_fields(&type_name, &fields, properties, None, span)?;
+
+    Ok((format!("{}::{} {{ {} }}", name.lexeme, variant.lexeme, fields.join(", ")), Type::Enum(name.lexeme.clone())))
+  }
+
+  // Campos de um literal como `campo: valor`. O spread vira o `..outro`
+  // do Rust, que só pode vir no fim e não existe para variantes de enum,
+  // então só é aceito como primeira propriedade e quando `base` é Some.
+  fn literal_fields(
+    &mut self,
+    type_name: &str,
+    declared: &[(String, Type)],
+    properties: &[Property],
+    base: Option<&Type>,
+    span: Span,
+  ) -> Result<Vec<String>, ShiftError> {
+    let mut fields = Vec::new();
+    let mut given = HashSet::new();
+    let mut spread = None;
+
+    for (i, property) in properties.iter().enumerate() {
+      match property {
+        Property::Spread(expression) if i == 0 && base.is_some() => {
+          let (code, ty) = self.owned(expression)?;
+          if Some(&ty) != base {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              expression.span(),
+              format!("Only a '{}' can be spread into a '{}', found {}.", type_name, type_name, ty),
+            ));
+          }
+          spread = Some(code);
+        },
+        Property::Spread(expression) => return Err(ShiftError::codegen(
+          codes::UNSUPPORTED,
+          expression.span(),
+          "'...' must be the first property of a struct literal to be transpiled.",
+        )),
+        Property::Field { key, key_span, value } => {
+          let Some((_, field)) = declared.iter().find(|(field, _)| field == key) else {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              *key_span,
+              format!("'{}' has no field '{}'.", type_name, key),
+            ));
+          };
+
+          let (code, ty) = self.coerced(value, field)?;
+          let code = match (field, &ty) {
+            (Type::Float, Type::Int) => format!("({}) as f64", code),
+            _ if *field == ty || ty == Type::Unknown => code,
+            (Type::Array(_), Type::Array(inner)) if **inner == Type::Unknown => code,
+            _ => return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              value.span(),
+              format!("Field '{}' of '{}' is {}, found {}.", key, type_name, field, ty),
+            )),
+          };
+
+          given.insert(key.clone());
+          fields.push(format!("{}: {}", key, code));
+        },
+      }
+    }
+
+    if spread.is_none()
+      && let Some((missing, _)) = declared.iter().find(|(field, _)| !given.contains(field))
+    {
+      return Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        span,
+        format!("Missing field '{}' in '{}'.", missing, type_name),
+      ));
+    }
+
+    if let Some(spread) = spread {
+      fields.push(format!("..{}", spread));
+    }
+    Ok(fields)
+  }
+
+  // `Shape` em `Shape.Empty`, quando não é uma variável
+  fn enum_name(&self, object: &Expr) -> Option<String> {
+    match object {
+      Expr::Variable { name, .. } if self.enums.contains_key(&name.lexeme) && self.lookup(&name.lexeme).is_none() => {
+        Some(name.lexeme.clone())
+      },
+      _ => None,
+    }
+  }
+
+  fn enum_info(&self, name: &Token) -> Result<EnumInfo, ShiftError> {
+    self.enums.get(&name.lexeme).cloned().ok_or_else(|| ShiftError::codegen(
+      codes::UNDEFINED_VARIABLE,
+      name.span,
+      format!("Undefined enum '{}'.", name.lexeme),
+    ))
+  }
+
+  fn unit_variant(&self, enum_name: &str, variant: &Token) -> Result<(String, Type), ShiftError> {
+    match self.enums[enum_name].variant(&variant.lexeme) {
+      Some(VariantInfo::Unit) => Ok((format!("{}::{}", enum_name, variant.lexeme), Type::Enum(enum_name.to_string()))),
+      Some(VariantInfo::Tuple(_)) => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        variant.span,
+        format!("'{}.{}' must be called with its values to be transpiled.", enum_name, variant.lexeme),
+      )),
+      Some(VariantInfo::Named(_)) => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        variant.span,
+        format!("'{}.{}' has named fields, create it with '{}.{} {{ ... }}'.", enum_name, variant.lexeme, enum_name, variant.lexeme),
+      )),
+      None => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        variant.span,
+        format!("Enum '{}' has no variant '{}'.", enum_name, variant.lexeme),
+      )),
+    }
+  }
+
+  // Shape.Circle(2) vira Shape::Circle(2 as f64)
+  fn tuple_variant(&mut self, enum_name: &str, variant: &Token, paren: &Token, arguments: &[Expr]) -> Result<(String, Type), ShiftError> {
+    let Some(VariantInfo::Tuple(types)) = self.enums[enum_name].variant(&variant.lexeme).cloned() else {
+      return Err(ShiftError::codegen(
+        codes::NOT_CALLABLE,
+        variant.span,
+        format!("'{}.{}' is not a variant that takes values.", enum_name, variant.lexeme),
+      ));
+    };
+
+    if types.len() != arguments.len() {
+      return Err(ShiftError::codegen(
+        codes::ARITY_MISMATCH,
+        paren.span,
+        format!("Expected {} arguments but got {}.", types.len(), arguments.len()),
+      ));
+    }
+
+    let mut args = Vec::with_capacity(arguments.len());
+    for (argument, expected) in arguments.iter().zip(&types) {
+      let (code, ty) = self.coerced(argument, expected)?;
+      args.push(match (expected, &ty) {
+        (Type::Float, Type::Int) => format!("({}) as f64", code),
+        _ if *expected == ty || ty == Type::Unknown => code,
+        (Type::Array(_), Type::Array(inner)) if **inner == Type::Unknown => code,
+        _ => return Err(ShiftError::codegen(
+          codes::TYPE_ERROR,
+          argument.span(),
+          format!("'{}.{}' expects {}, found {}.", enum_name, variant.lexeme, expected, ty),
+        )),
+      });
+    }
+
+    Ok((format!("{}::{}({})", enum_name, variant.lexeme, args.join(", ")), Type::Enum(enum_name.to_string())))
+  }
+
+  // ---------- Match ----------
+
+  // Valor do `match` e o tipo que os padrões conferem. Strings viram `&str`
+  // para os padrões literais funcionarem; variáveis são clonadas, já que
+  // os padrões movem o que capturam.
+  fn match_subject(&mut self, subject: &Expr) -> Result<(String, Type), ShiftError> {
+    let (code, ty) = self.owned(subject)?;
+
+    if ty == Type::Str {
+      return Ok((format!("{}.as_str()", receiver(code)), ty));
+    }
+    Ok((code, ty))
+  }
+
+  // Sem um braço que sempre casa, o Rust exige o `_` que o interpretador
+  // trata como erro em tempo de execução
+  fn is_exhaustive(&self, arms: &[MatchArm], ty: &Type) -> bool {
+    let unguarded = || arms.iter().filter(|arm| arm.guard.is_none()).map(|arm| &arm.pattern);
+    if unguarded().any(Pattern::is_irrefutable) {
+      return true;
+    }
+
+    if *ty == Type::Bool {
+      let covers = |expected: bool| unguarded().any(|pattern| {
+        matches!(pattern, Pattern::Literal { value: LiteralValue::Boolean(b), .. } if *b == expected)
+      });
+      return covers(true) && covers(false);
+    }
+
+    let variants_of = |name: &str| self.enums.get(name).map(|info| info.variants.iter().map(|(variant, _)| variant.clone()).collect());
+    matches!(lint::missing_variants(arms, variants_of), Some((_, missing)) if missing.is_empty())
+  }
+
+  // Padrão do braço e o escopo com as variáveis que ele cria
+  fn arm_pattern(&mut self, arm: &MatchArm, ty: &Type) -> Result<(String, Vec<(String, Type)>), ShiftError> {
+    let mut bindings = Vec::new();
+    let mut code = self.pattern(&arm.pattern, ty, &mut bindings)?;
+
+    if let Some(guard) = &arm.guard {
+      self.scopes.push(HashMap::new());
+      for (name, ty) in &bindings {
+        self.define(name, ty.clone(), true);
+      }
+      let guard = self.condition(guard);
+      self.scopes.pop();
+      code = format!("{} if {}", code, guard?);
+    }
+
+    Ok((code, bindings))
+  }
+
+  // `x` num match de string captura um `&str`: vira String de novo no corpo
+  fn rebind_strings(&self, arm: &MatchArm, ty: &Type) -> Option<String> {
+    match &arm.pattern {
+      Pattern::Binding(name) if *ty == Type::Str => Some(format!("let mut {} = {}.to_string();", name.lexeme, name.lexeme)),
+      _ => None,
+    }
+  }
+
+  fn match_expression(&mut self, subject: &Expr, arms: &[MatchArm]) -> Result<(String, Type), ShiftError> {
+    let (subject_code, subject_ty) = self.match_subject(subject)?;
+    let mut patterns = Vec::new();
+    let mut bodies = Vec::new();
+
+    for arm in arms {
+      let (pattern, bindings) = self.arm_pattern(arm, &subject_ty)?;
+      let Stmt::Expression { expression } = &arm.body else {
+        unreachable!("o parser só aceita blocos no match usado como instrução")
+      };
+
+      self.scopes.push(HashMap::new());
+      for (name, ty) in bindings {
+        self.define(&name, ty, true);
+      }
+      let body = self.owned(expression);
+      self.scopes.pop();
+
+      let (code, ty) = body?;
+      let code = match self.rebind_strings(arm, &subject_ty) {
+        Some(rebind) => format!("{{ {} {} }}", rebind, code),
+        None => code,
+      };
+      patterns.push(pattern);
+      bodies.push((code, ty, expression.span()));
+    }
+
+    // Como no ternário, int e float se misturam como float
+    let known: Vec<&Type> = bodies.iter().map(|(_, ty, _)| ty).filter(|ty| **ty != Type::Unknown).collect();
+    let ty = if known.contains(&&Type::Float) && known.iter().all(|ty| matches!(ty, Type::Int | Type::Float)) {
+      Type::Float
+    } else {
+      known.first().map_or(Type::Unknown, |ty| (*ty).clone())
+    };
+
+    let mut arms_code = Vec::new();
+    for (pattern, (code, body_ty, span)) in patterns.into_iter().zip(bodies) {
+      let code = match (&ty, &body_ty) {
+        (Type::Float, Type::Int) => format!("({}) as f64", code),
+        _ if body_ty == ty || body_ty == Type::Unknown => code,
+        _ => return Err(ShiftError::codegen(
+          codes::TYPE_ERROR,
+          span,
+          format!("All match arms must have the same type, found {} and {}.", ty, body_ty),
+        )),
+      };
+      arms_code.push(format!("{} => {}", pattern, code));
+    }
+
+    if !self.is_exhaustive(arms, &subject_ty) {
+      arms_code.push(format!("_ => panic!({:?})", NO_MATCH_MESSAGE));
+    }
+
+    Ok((format!("match {} {{ {} }}", subject_code, arms_code.join(", ")), ty))
+  }
+
+  fn match_statement(&mut self, subject: &Expr, arms: &[MatchArm]) -> Result<(), ShiftError> {
+    let (subject_code, subject_ty) = self.match_subject(subject)?;
+    self.line(&format!("match {} {{", subject_code));
+    self.indent += 1;
+
+    for arm in arms {
+      let (pattern, bindings) = self.arm_pattern(arm, &subject_ty)?;
+      self.line(&format!("{} => {{", pattern));
+
+      self.scopes.push(HashMap::new());
+      for (name, ty) in bindings {
+        self.define(&name, ty, true);
+      }
+      if let Some(rebind) = self.rebind_strings(arm, &subject_ty) {
+        self.indent += 1;
+        self.line(&rebind);
+        self.indent -= 1;
+      }
+      let body = self.body(&arm.body);
+      self.scopes.pop();
+      body?;
+
+      self.line("}");
+    }
+
+    if !self.is_exhaustive(arms, &subject_ty) {
+      self.line(&format!("_ => panic!({:?}),", NO_MATCH_MESSAGE));
+    }
+
+    self.indent -= 1;
+    self.line("}");
+    Ok(())
+  }
+
+  fn pattern(&mut self, pattern: &Pattern, ty: &Type, bindings: &mut Vec<(String, Type)>) -> Result<String, ShiftError> {
+    match pattern {
+      Pattern::Wildcard(_) => Ok("_".to_string()),
+
+      Pattern::Binding(name) => {
+        bindings.push((name.lexeme.clone(), ty.clone()));
+        // Strings chegam como &str e são convertidas no corpo
+        if *ty == Type::Str && bindings.len() == 1 {
+          return Ok(name.lexeme.clone());
+        }
+        Ok(format!("mut {}", name.lexeme))
+      },
+
+      Pattern::Literal { value, span } => self.literal_pattern(value, ty, *span, pattern),
+
+      Pattern::Range { start, end, span } => {
+        let start = self.literal_pattern(start, ty, *span, pattern)?;
+        let end = self.literal_pattern(end, ty, *span, pattern)?;
+        Ok(format!("{}..{}", start, end))
+      },
+
+      Pattern::Variant { enum_name, variant, fields, span } => {
+        let info = self.enum_info(enum_name)?;
+        if !matches!(ty, Type::Unknown) && *ty != Type::Enum(enum_name.lexeme.clone()) {
+          return Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            *span,
+            format!("Pattern '{}' cannot match a value of type {}.", pattern, ty),
+          ));
+        }
+
+        let path = format!("{}::{}", enum_name.lexeme, variant.lexeme);
+        match (fields, info.variant(&variant.lexeme)) {
+          (_, None) => Err(undefined_variant(enum_name, variant)),
+          (FieldPatterns::Unit, Some(VariantInfo::Unit)) => Ok(path),
+          (FieldPatterns::Tuple(patterns), Some(VariantInfo::Tuple(types))) if patterns.len() == types.len() => {
+            let mut parts = Vec::new();
+            for (pattern, ty) in patterns.iter().zip(types) {
+              parts.push(self.payload_pattern(pattern, ty, bindings)?);
+            }
+            Ok(format!("{}({})", path, parts.join(", ")))
+          },
+          (FieldPatterns::Named(patterns), Some(VariantInfo::Named(declared))) => {
+            let mut parts = Vec::new();
+            for (name, pattern) in patterns {
+              let Some((_, ty)) = declared.iter().find(|(field, _)| *field == name.lexeme) else {
+                return Err(ShiftError::codegen(
+                  codes::TYPE_ERROR,
+                  name.span,
+                  format!("'{}.{}' has no field '{}'.", enum_name.lexeme, variant.lexeme, name.lexeme),
+                ));
+              };
+
+              let code = self.payload_pattern(pattern, ty, bindings)?;
+              match pattern {
+                Pattern::Binding(binding) if binding.lexeme == name.lexeme => parts.push(code),
+                _ => parts.push(format!("{}: {}", name.lexeme, code)),
+              }
+            }
+            parts.push("..".to_string());
+            Ok(format!("{} {{ {} }}", path, parts.join(", ")))
+          },
+          _ => Err(ShiftError::codegen(
+            codes::TYPE_ERROR,
+            *span,
+            format!("Pattern '{}' does not fit the fields of '{}.{}'.", pattern, enum_name.lexeme, variant.lexeme),
+          )),
+        }
+      },
+    }
+  }
+
+  // Dentro de variantes as strings são String, que não casa com literais
+  fn payload_pattern(&mut self, pattern: &Pattern, ty: &Type, bindings: &mut Vec<(String, Type)>) -> Result<String, ShiftError> {
+    match pattern {
+      Pattern::Binding(name) => {
+        bindings.push((name.lexeme.clone(), ty.clone()));
+        Ok(format!("mut {}", name.lexeme))
+      },
+      Pattern::Literal { span, .. } | Pattern::Range { span, .. } if *ty == Type::Str => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        *span,
+        "String patterns inside variants cannot be transpiled yet.",
+      ).with_help("bind the value and compare it in a guard: 'Msg(text) if text == \"oi\"'")),
+      pattern => self.pattern(pattern, ty, bindings),
+    }
+  }
+
+  fn literal_pattern(&self, value: &LiteralValue, ty: &Type, span: Span, pattern: &Pattern) -> Result<String, ShiftError> {
+    let mismatch = || ShiftError::codegen(
+      codes::TYPE_ERROR,
+      span,
+      format!("Pattern '{}' cannot match a value of type {}.", pattern, ty),
+    );
+
+    match (value, ty) {
+      (LiteralValue::Integer(i), Type::Float) => Ok(format!("{:?}", *i as f64)),
+      (LiteralValue::Integer(i), Type::Int | Type::Unknown) => Ok(i.to_string()),
+      (LiteralValue::Float(f), Type::Float | Type::Unknown) => Ok(format!("{:?}", f)),
+      (LiteralValue::String(s), Type::Str | Type::Unknown) => Ok(format!("{:?}", s)),
+      (LiteralValue::Boolean(b), Type::Bool | Type::Unknown) => Ok(b.to_string()),
+      (LiteralValue::Null, _) => Err(ShiftError::codegen(
+        codes::UNSUPPORTED,
+        span,
+        "'null' cannot be transpiled to Rust yet.",
+      )),
+      _ => Err(mismatch()),
+    }
+  }
+
+  // [a, ...b, c] vira `[vec![a], b.clone(), vec![c]].concat()`
+  // `expected` é o tipo dos elementos quando o lugar já tem um
+  fn array(&mut self, elements: &[Expr], expected: &Type) -> Result<(String, Type), ShiftError> {
+    let mut element_ty = Type::Unknown;
+    let mut parts = Vec::new();
+    let mut run = Vec::new();
+    let mut spreads = false;
+
+    for element in elements {
+      let (code, ty) = match element {
+        Expr::Spread { expression, .. } => {
+          let (code, ty) = self.expression(expression)?;
+          let Type::Array(inner) = ty else {
+            return Err(ShiftError::codegen(
+              codes::TYPE_ERROR,
+              expression.span(),
+              format!("Only arrays can be spread, found {}.", ty),
+            ));
+          };
+
+          // A lista é nova, só os itens são copiados
+          self.share(expression, &inner)?;
+          let code = match expression.as_ref() {
+            Expr::Variable { .. } | Expr::Get { .. } => format!("{}.clone()", code),
+            _ => code,
+          };
+
+          spreads = true;
+          if !run.is_empty() {
+            parts.push(format!("vec![{}]", run.join(", ")));
+            run.clear();
+          }
+          parts.push(code.clone());
+          (code, *inner)
+        },
+        element => {
+          let (code, ty) = self.coerced(element, expected)?;
+          run.push(code.clone());
+          (code, ty)
+        },
+      };
+
+      if element_ty == Type::Unknown {
+        element_ty = ty;
+      } else if ty != element_ty && ty != Type::Unknown {
+        return Err(ShiftError::codegen(
+          codes::TYPE_ERROR,
+          element.span(),
+          format!("Array elements must all have the same type, found {} and {} ('{}').", element_ty, ty, code),
+        ));
+      }
+    }
+
+    let ty = Type::Array(Box::new(element_ty));
+    if !spreads {
+      return Ok((format!("vec![{}]", run.join(", ")), ty));
+    }
+
+    if !run.is_empty() {
+      parts.push(format!("vec![{}]", run.join(", ")));
+    }
+    Ok((format!("[{}].concat()", parts.join(", ")), ty))
+  }
+
+  // Objeto indexado e o tipo dos seus elementos
+  // Para gravar, o objeto também vira um lugar: `grid[0][1] = 9` não pode
+  // gravar numa cópia de `grid[0]`
+  fn indexed(&mut self, object: &Expr, writing: bool) -> Result<(String, Type), ShiftError> {
+    let (code, ty) = if writing { self.place(object)? } else { self.expression(object)? };
+
+    match ty {
+      Type::Array(inner) => Ok((code, *inner)),
+      Type::Unknown => Ok((code, Type::Unknown)),
+      ty => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        object.span(),
+        format!("Only arrays can be indexed, found {}.", ty),
+      )),
+    }
+  }
+
+  fn index(&mut self, index: &Expr) -> Result<String, ShiftError> {
+    let (code, ty) = self.expression(index)?;
+
+    match ty {
+      Type::Int | Type::Unknown => Ok(code),
+      ty => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        index.span(),
+        format!("Array index must be an int, found {}.", ty),
+      )),
+    }
+  }
+
+  fn is_string(&mut self, expr: &Expr) -> Result<bool, ShiftError> {
+    let Expr::Binary { left, operator, right, .. } = expr else {
+      return Ok(false);
+    };
+
+    let (_, lt) = self.expression(left)?;
+    let (_, rt) = self.expression(right)?;
+
+    match (lt, rt) {
+      (Type::Str, Type::Str) => Ok(true),
+      (Type::Str, _) | (_, Type::Str) => Err(ShiftError::codegen(
+        codes::TYPE_ERROR,
+        operator.span,
+        "The operator '+' requires two numbers or two strings.",
+      )),
+      _ => Ok(false),
+    }
+  }
+
+  // Achata `"a" + b + "c"` em `("a{}c", [b])` para um único format!
+  fn format_parts(&mut self, expr: &Expr) -> Result<(String, Vec<String>), ShiftError> {
+    let mut format = String::new();
+    let mut args = Vec::new();
+    self.collect_format(expr, &mut format, &mut args)?;
+
+    Ok((format, args))
+  }
+
+  fn collect_format(&mut self, expr: &Expr, format: &mut String, args: &mut Vec<String>) -> Result<(), ShiftError> {
+    match expr {
+      Expr::Literal { value: LiteralValue::String(s), .. } => {
+        format.push_str(&s.replace('{', "{{").replace('}', "}}"));
+        Ok(())
+      },
+
+      Expr::Binary { left, operator, right, .. } if operator.token_type == TokenType::Plus && self.is_string(expr)? => {
+        self.collect_format(left, format, args)?;
+        self.collect_format(right, format, args)
+      },
+
+      Expr::Grouping { expression, .. } => self.collect_format(expression, format, args),
+
+      Expr::Template { parts, .. } => {
+        parts.iter().try_for_each(|part| self.collect_format(part, format, args))
+      },
+
+      other => {
+        let (code, ty) = self.expression(other)?;
+        if matches!(ty, Type::Unit | Type::Function { .. }) {
+          return Err(ShiftError::codegen(
+            codes::UNSUPPORTED,
+            other.span(),
+            format!("Value '{}' cannot be displayed in Rust output.", other),
+          ));
+        }
+
+        if ty.is_null() {
+          format.push_str("null");
+          return Ok(());
+        }
+
+        // Vec, structs, enums e traits não implementam Display
+        let debug = |ty: &Type| matches!(ty, Type::Array(_) | Type::Struct(_) | Type::Enum(_) | Type::Trait(_));
+        match &ty {
+          Type::Option(inner) => {
+            let shown = if debug(inner) { "format!(\"{:?}\", value)" } else { "value.to_string()" };
+            format.push_str("{}");
+            args.push(format!("{}.as_ref().map_or_else(|| String::from(\"null\"), |value| {})", receiver(code), shown));
+          },
+          ty => {
+            format.push_str(if debug(ty) { "{:?}" } else { "{}" });
+            args.push(code);
+          },
+        }
+        Ok(())
+      },
+    }
+  }
+
+  fn builtin_call(&mut self, name: &Token, arguments: &[Expr]) -> Result<(String, Type), ShiftError> {
+    let expect = |count: usize| {
+      if arguments.len() == count {
+        Ok(())
+      } else {
+        Err(ShiftError::codegen(
+          codes::ARITY_MISMATCH,
+          name.span,
+          format!("Expected {} arguments but got {}.", count, arguments.len()),
+        ))
+      }
+    };
+
+    match name.lexeme.as_str() {
+      "print" => {
+        expect(1)?;
+        let (format, args) = self.format_parts(&arguments[0])?;
+        Ok((format_call("println!", &format, &args), Type::Unit))
+      },
+
+      "len" => {
+        expect(1)?;
+        let (code, ty) = self.expression(&arguments[0])?;
+        match ty {
+          Type::Array(_) => Ok((format!("({}.len() as i64)", code), Type::Int)),
+          Type::Str | Type::Unknown => Ok((format!("({}.chars().count() as i64)", code), Type::Int)),
+          _ => Err(ShiftError::codegen(codes::TYPE_ERROR, name.span, "len() expects a string or an array.")),
+        }
+      },
+
+      "clock" => {
+        expect(0)?;
+        let code = "std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs_f64()";
+        Ok((code.to_string(), Type::Float))
+      },
+
+      _ => Err(ShiftError::codegen(
+        codes::UNDEFINED_VARIABLE,
+        name.span,
+        format!("Undefined variable '{}' (Rust functions cannot capture variables from outside).", name.lexeme),
+      )),
+    }
+  }
+
+  // Closures capturam o escopo atual, diferente dos itens `fn`
+  fn closure(&mut self, declaration: &FunctionDecl) -> Result<(String, Type), ShiftError> {
+    let mut scope = HashMap::new();
+    let mut params = Vec::new();
+    let mut param_list = Vec::new();
+
+    for param in &declaration.params {
+      let ty = param.type_annotation.as_ref().map(|annotation| self.annotation(annotation)).transpose()?.unwrap_or(Type::Unknown);
+      match ty.rust_name() {
+        Some(rust) => param_list.push(format!("{}: {}", param.name.lexeme, rust)),
+        None => param_list.push(param.name.lexeme.clone()),
+      }
+      self.check_param(&self.changes, &param.name, &ty)?;
+      scope.insert(param.name.lexeme.clone(), Binding { ty: ty.clone(), mutable: true });
+      params.push(ty);
+    }
+
+    self.scopes.push(scope);
+
+    // `x => x * 2` vira `|x| x * 2`
+    let result = if let [Stmt::Return { value: Some(value) }] = declaration.body.as_slice() {
+      self.owned(value).map(|(code, ty)| (format!("|{}| {}", param_list.join(", "), code), ty))
+    } else {
+      self.closure_block(declaration, &param_list)
+    };
+
+    self.scopes.pop();
+    let (code, ret) = result?;
+
+    Ok((code, Type::Function { params, ret: Box::new(ret) }))
+  }
+
+  fn closure_block(&mut self, declaration: &FunctionDecl, param_list: &[String]) -> Result<(String, Type), ShiftError> {
+    let output = std::mem::take(&mut self.output);
+    let return_type = self.return_type.replace(Type::Unknown);
+
+    self.output.push_str(&format!("|{}| {{\n", param_list.join(", ")));
+    let result = self.block(&declaration.body);
+    for _ in 0..self.indent {
+      self.output.push_str("    ");
+    }
+    self.output.push('}');
+
+    let code = std::mem::replace(&mut self.output, output);
+    self.return_type = return_type;
+    result?;
+
+    let ret = if returns_value(&declaration.body) { Type::Unknown } else { Type::Unit };
+    Ok((code, ret))
+  }
+}
+
+impl Default for CodeGenerator {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+fn declaration_line(keyword: &str, name: &str, ty: &Type, value: Option<&str>) -> String {
+  let mut line = format!("{} {}", keyword, name);
+
+  if let Some(rust) = ty.rust_name() {
+    line.push_str(&format!(": {}", rust));
+  }
+  if let Some(value) = value {
+    line.push_str(&format!(" = {}", value));
+  }
+
+  line.push(';');
+  line
+}
+
+// Índices negativos contam do fim, como no interpretador
+const INDEX_HELPERS: &str = "\
+fn position(len: usize, index: i64) -> usize {
+    let resolved = if index < 0 { index + len as i64 } else { index };
+    if resolved < 0 || resolved as usize >= len {
+        panic!(\"Index {} is out of bounds for an array of length {}.\", index, len);
+    }
+    resolved as usize
+}
+
+fn at<T>(items: &[T], index: i64) -> &T {
+    &items[position(items.len(), index)]
+}
+
+fn at_mut<T>(items: &mut [T], index: i64) -> &mut T {
+    let i = position(items.len(), index);
+    &mut items[i]
+}
+";
+
+// Aritmética inteira checada, com as mesmas mensagens do interpretador
+const ARITHMETIC_HELPERS: &str = "\
+fn checked(result: Option<i64>, operator: &str) -> i64 {
+    result.unwrap_or_else(|| panic!(\"Integer overflow in '{}'.\", operator))
+}
+
+fn divisor(value: i64, message: &str) -> i64 {
+    if value == 0 {
+        panic!(\"{}\", message);
+    }
+    value
+}
+
+fn apply(value: i64, slot: &mut i64, operate: impl Fn(i64, i64) -> i64) {
+    *slot = operate(*slot, value);
+}
+";
+
+fn function_header(name: &str, params: &[String], ret: &Type) -> String {
+  match ret {
+    Type::Unit => format!("fn {}({})", name, params.join(", ")),
+    ty => format!("fn {}({}) -> {}", name, params.join(", "), ty.rust_name().unwrap_or_else(|| "_".to_string())),
+  }
+}
+
+// `fn show(self, int) -> string`, para as mensagens de erro
+fn signature_text(name: &str, ty: &Type) -> String {
+  let Type::Function { params, ret } = ty else { unreachable!() };
+  let params: Vec<String> = std::iter::once("self".to_string()).chain(params.iter().map(Type::to_string)).collect();
+
+  match ret.as_ref() {
+    Type::Unit => format!("fn {}({})", name, params.join(", ")),
+    ret => format!("fn {}({}) -> {}", name, params.join(", "), ret),
+  }
+}
+
+const NO_MATCH_MESSAGE: &str = "No match arm matched the value.";
+
+fn undefined_variant(enum_name: &Token, variant: &Token) -> ShiftError {
+  ShiftError::codegen(
+    codes::TYPE_ERROR,
+    variant.span,
+    format!("Enum '{}' has no variant '{}'.", enum_name.lexeme, variant.lexeme),
+  )
+}
+
+// Se um `?.` antes na cadeia pode ter parado a avaliação
+fn in_optional_chain(expr: &Expr) -> bool {
+  match expr {
+    Expr::Get { optional: true, .. } => true,
+    Expr::Get { object, .. } | Expr::Index { object, .. } => in_optional_chain(object),
+    Expr::Call { callee, .. } => in_optional_chain(callee),
+    _ => false,
+  }
+}
+
+// `*at_mut(..)` precisa de parênteses antes de `.campo`
+fn receiver(code: String) -> String {
+  if code.starts_with('*') {
+    format!("({})", code)
+  } else {
+    code
+  }
+}
+
+fn format_call(macro_name: &str, format: &str, args: &[String]) -> String {
+  if args.is_empty() {
+    return format!("{}({:?})", macro_name, format);
+  }
+
+  format!("{}({:?}, {})", macro_name, format, args.join(", "))
+}
+
+// Procura `return valor` sem entrar em funções aninhadas
+fn returns_value(statements: &[Stmt]) -> bool {
+  statements.iter().any(|statement| match statement {
+    Stmt::Return { value } => value.is_some(),
+    Stmt::Block { statements } => returns_value(statements),
+    Stmt::If { then_branch, else_branch, .. } => {
+      returns_value(std::slice::from_ref(then_branch.as_ref()))
+        || else_branch.as_ref().is_some_and(|branch| returns_value(std::slice::from_ref(branch.as_ref())))
+    },
+    Stmt::While { body, .. } | Stmt::Loop { body } | Stmt::For { body, .. } => {
+      returns_value(std::slice::from_ref(body.as_ref()))
+    },
+    Stmt::Match { arms, .. } => arms.iter().any(|arm| returns_value(std::slice::from_ref(&arm.body))),
+    _ => false,
+  })
+}
+
+// Métodos que gravam em `self`, direto ou chamando outro método que grava,
+// recebem `&mut self`. Os nomes valem para todos os structs, então um nome
+// repetido pode pedir `&mut self` sem precisar.
+fn mutating_methods<'a>(methods: impl Iterator<Item = &'a Method> + Clone) -> HashSet<String> {
+  let mut mutating = HashSet::new();
+
+  loop {
+    let before = mutating.len();
+    for method in methods.clone().filter(|method| method.has_self) {
+      if method.declaration.body.iter().any(|statement| stmt_mutates_self(statement, &mutating))
+        && let Some(name) = &method.declaration.name
+      {
+        mutating.insert(name.lexeme.clone());
+      }
+    }
+
+    if mutating.len() == before {
+      return mutating;
+    }
+  }
+}
+
+fn stmt_mutates_self(stmt: &Stmt, mutating: &HashSet<String>) -> bool {
+  let expr = |expr: &Expr| expr_mutates_self(expr, mutating);
+  let body = |stmt: &Stmt| stmt_mutates_self(stmt, mutating);
+
+  match stmt {
+    Stmt::Expression { expression } => expr(expression),
+    Stmt::Let { initializer, .. } => initializer.as_ref().is_some_and(expr),
+    Stmt::Const { initializer, .. } => expr(initializer),
+    Stmt::Block { statements } => statements.iter().any(body),
+    Stmt::If { condition, then_branch, else_branch } => {
+      expr(condition) || body(then_branch) || else_branch.as_deref().is_some_and(body)
+    },
+    Stmt::While { condition, body: inner } => expr(condition) || body(inner),
+    Stmt::Loop { body: inner } => body(inner),
+    Stmt::For { start, end, body: inner, .. } => expr(start) || expr(end) || body(inner),
+    Stmt::Return { value } => value.as_ref().is_some_and(expr),
+    Stmt::Match { subject, arms } => {
+      expr(subject) || arms.iter().any(|arm| arm.guard.as_ref().is_some_and(expr) || body(&arm.body))
+    },
+    Stmt::Break | Stmt::Continue | Stmt::Function { .. } | Stmt::Struct { .. } | Stmt::Impl { .. } | Stmt::Enum { .. } | Stmt::Trait { .. } => {
+      false
+    },
+  }
+}
+
+fn expr_mutates_self(expr: &Expr, mutating: &HashSet<String>) -> bool {
+  let writes = match expr {
+    Expr::Set { object, .. } | Expr::SetIndex { object, .. } => is_self(object),
+    Expr::CompoundAssign { target, .. } | Expr::Update { target, .. } => is_self(target),
+    Expr::Call { callee, .. } => matches!(callee.as_ref(), Expr::Get { object, name, .. }
+      if is_self(object) && mutating.contains(&name.lexeme)),
+    _ => false,
+  };
+
+  writes || expr.children().into_iter().any(|child| expr_mutates_self(child, mutating))
+}
+
+// `self`, `self.campo`, `self.lista[0]`...
+fn is_self(expr: &Expr) -> bool {
+  match expr {
+    Expr::Variable { name, .. } => name.lexeme == "self",
+    Expr::Index { object, .. } | Expr::Get { object, .. } => is_self(object),
+    _ => false,
+  }
+}
+
+const SHARED_HELP: &str = "arrays and structs are shared between variables in Shift but copied in Rust";
+
+// Arrays e structs, que no interpretador são compartilhados
+fn is_shared(ty: &Type) -> bool {
+  matches!(ty.without_null(), Type::Array(_) | Type::Struct(_))
+}
+
+// Variável por trás de `a`, `a.campo` ou `a[0].campo`, com quantos
+// campos e itens foram atravessados até ela
+fn root_of(expr: &Expr) -> Option<(&str, usize)> {
+  match expr {
+    Expr::Variable { name, .. } => Some((&name.lexeme, 0)),
+    Expr::Index { object, .. } | Expr::Get { object, .. } => root_of(object).map(|(root, depth)| (root, depth + 1)),
+    Expr::Grouping { expression, .. } => root_of(expression),
+    _ => None,
+  }
+}
+
+// Se a função pode devolver um parâmetro (ou `self`) ou guardá-lo em
+// algum lugar, direto ou por uma variável local. Nesse caso o valor
+// continua compartilhado com o argumento depois da chamada.
+fn keeps_arguments(declaration: &FunctionDecl) -> bool {
+  let mentions = |held: &HashSet<String>, expr: &Expr| {
+    let mut found = false;
+    lint::walk_expr(expr, &mut |node| {
+      if let lint::Node::Expr(Expr::Variable { name, .. }) = node {
+        found |= held.contains(&name.lexeme);
+      }
+    });
+    found
+  };
+
+  let mut held: HashSet<String> = declaration.params.iter().map(|param| param.name.lexeme.clone()).collect();
+  held.insert("self".to_string());
+
+  loop {
+    let mut keeps = false;
+    let mut more = Vec::new();
+    lint::walk(&declaration.body, &mut |node| match node {
+      lint::Node::Stmt(Stmt::Let { name, initializer: Some(value), .. } | Stmt::Const { name, initializer: value, .. })
+        if mentions(&held, value) => more.push(name.lexeme.clone()),
+      lint::Node::Expr(Expr::Assign { name, value, .. }) if mentions(&held, value) => more.push(name.lexeme.clone()),
+      lint::Node::Stmt(Stmt::Return { value: Some(value) }) => keeps |= mentions(&held, value),
+      lint::Node::Expr(Expr::Set { value, .. } | Expr::SetIndex { value, .. }) => keeps |= mentions(&held, value),
+      _ => {},
+    });
+
+    let before = held.len();
+    held.extend(more);
+    if keeps || held.len() == before {
+      return keeps;
+    }
+  }
+}
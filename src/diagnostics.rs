@@ -0,0 +1,122 @@
+use std::fmt::Write;
+
+use crate::errors::{ErrorKind, Note, ShiftError};
+use crate::token::Span;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// Mostra erros no estilo do rustc:
+//
+// error[E301]: The operator '+' requires two numbers or two strings
+//  --> main.st:2:9
+//   |
+// 2 | print(s + 1)
+//   |         ^
+//   |
+//   = help: ...
+pub struct Renderer<'a> {
+  source: &'a str,
+  path: &'a str,
+  color: bool,
+}
+
+impl<'a> Renderer<'a> {
+  pub fn new(source: &'a str, path: &'a str) -> Self {
+    Self { source, path, color: false }
+  }
+
+  pub fn colored(mut self, color: bool) -> Self {
+    self.color = color;
+    self
+  }
+
+  pub fn render(&self, error: &ShiftError) -> String {
+    let mut out = String::new();
+    let (label, main_color) = if error.kind == ErrorKind::Warning { ("warning", YELLOW) } else { ("error", RED) };
+
+    let _ = writeln!(
+      out,
+      "{}{}[{}]{}{}: {}{}",
+      self.paint(main_color), label, error.code, self.paint(RESET), self.paint(BOLD), error.message, self.paint(RESET)
+    );
+
+    let Some(span) = error.span else {
+      self.help(&mut out, error, 0);
+      return out;
+    };
+
+    // Trechos destacados: o principal com ^^^, os rótulos extras com ---
+    let mut marks: Vec<(Span, char, &str)> = vec![(span, '^', "")];
+    for note in &error.notes {
+      if let Note::Label { span, message } = note {
+        marks.push((*span, '-', message));
+      }
+    }
+    marks.sort_by_key(|(span, ..)| (span.line, span.column));
+
+    let width = marks.iter().map(|(span, ..)| span.line).max().unwrap_or(1).to_string().len();
+    let gutter = " ".repeat(width);
+
+    let _ = writeln!(out, "{}{}-->{} {}:{}:{}", gutter, self.paint(BLUE), self.paint(RESET), self.path, span.line, span.column);
+    let _ = writeln!(out, "{} {}|{}", gutter, self.paint(BLUE), self.paint(RESET));
+
+    let mut last_line = 0;
+    for (span, marker, message) in &marks {
+      let Some(text) = self.source.lines().nth(span.line.saturating_sub(1)) else {
+        continue;
+      };
+
+      if span.line != last_line {
+        let _ = writeln!(
+          out,
+          "{}{:>width$} |{} {}",
+          self.paint(BLUE), span.line, self.paint(RESET), text.replace('\t', " "),
+          width = width
+        );
+        last_line = span.line;
+      }
+
+      let color = if *marker == '^' { main_color } else { BLUE };
+      let padding = " ".repeat(span.column.saturating_sub(1));
+      let underline = marker.to_string().repeat(self.underline_width(*span, text));
+      let message = if message.is_empty() { String::new() } else { format!(" {}", message) };
+
+      let _ = writeln!(
+        out,
+        "{} {}|{} {}{}{}{}{}",
+        gutter, self.paint(BLUE), self.paint(RESET), padding, self.paint(color), underline, message, self.paint(RESET)
+      );
+    }
+
+    self.help(&mut out, error, width);
+    out
+  }
+
+  fn help(&self, out: &mut String, error: &ShiftError, width: usize) {
+    let gutter = " ".repeat(width);
+
+    for note in &error.notes {
+      if let Note::Help(help) = note {
+        let _ = writeln!(out, "{} {}|{}", gutter, self.paint(BLUE), self.paint(RESET));
+        let _ = writeln!(out, "{} {}= help:{} {}", gutter, self.paint(CYAN), self.paint(RESET), help);
+      }
+    }
+  }
+
+  // Quantos caracteres sublinhar, sem passar do fim da linha
+  fn underline_width(&self, span: Span, text: &str) -> usize {
+    let covered = self.source.get(span.start..span.end).map_or(0, |slice| slice.chars().count());
+    let remaining = text.chars().count().saturating_sub(span.column.saturating_sub(1));
+
+    covered.min(remaining).max(1)
+  }
+
+  fn paint(&self, code: &'static str) -> &'static str {
+    if self.color { code } else { "" }
+  }
+}
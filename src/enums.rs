@@ -0,0 +1,90 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::interpreter::RuntimeValue;
+use crate::object::Object;
+
+// O tipo criado por `enum Shape { ... }`
+#[derive(Debug)]
+pub struct EnumDef {
+  pub name: String,
+  pub variants: Vec<VariantDef>,
+}
+
+#[derive(Debug)]
+pub struct VariantDef {
+  pub name: String,
+  pub kind: VariantKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariantKind {
+  Unit,
+  // Quantos valores a variante leva
+  Tuple(usize),
+  Named(Vec<String>),
+}
+
+impl EnumDef {
+  pub fn variant(&self, name: &str) -> Option<usize> {
+    self.variants.iter().position(|variant| variant.name == name)
+  }
+}
+
+impl fmt::Display for EnumDef {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<enum {}>", self.name)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+  Unit,
+  Tuple(Vec<RuntimeValue>),
+  Named(Object),
+}
+
+// Valor de uma variante, como `Shape.Circle(2)`
+#[derive(Debug, Clone)]
+pub struct EnumValue {
+  pub def: Rc<EnumDef>,
+  pub variant: usize,
+  pub payload: Payload,
+}
+
+impl EnumValue {
+  pub fn name(&self) -> &str {
+    &self.def.variants[self.variant].name
+  }
+}
+
+// Diferente de structs, variantes comparam pelo conteúdo:
+// `forma == Shape.Empty` funciona
+impl PartialEq for EnumValue {
+  fn eq(&self, other: &Self) -> bool {
+    Rc::ptr_eq(&self.def, &other.def) && self.variant == other.variant && self.payload == other.payload
+  }
+}
+
+// Igual ao `{:?}` do Rust, para o interpretador e o código gerado imprimirem o mesmo
+impl fmt::Display for EnumValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match &self.payload {
+      Payload::Unit => write!(f, "{}", self.name()),
+      Payload::Tuple(values) => {
+        write!(f, "{}(", self.name())?;
+        for (i, value) in values.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          match value {
+            RuntimeValue::String(s) => write!(f, "{:?}", s)?,
+            value => write!(f, "{}", value)?,
+          }
+        }
+        write!(f, ")")
+      },
+      Payload::Named(fields) => write!(f, "{} {}", self.name(), fields),
+    }
+  }
+}
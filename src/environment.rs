@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::errors::{codes, ShiftError};
+use crate::interpreter::RuntimeValue;
+use crate::token::Token;
+
+#[derive(Debug, Clone)]
+struct Binding {
+  value: RuntimeValue,
+  constant: bool,
+}
+
+// Um escopo léxico. O escopo global não tem `enclosing`,
+// blocos aninhados apontam para o escopo onde foram criados.
+#[derive(Debug, Default)]
+pub struct Environment {
+  values: HashMap<String, Binding>,
+  enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn new_enclosed(enclosing: Rc<RefCell<Environment>>) -> Self {
+    Self {
+      values: HashMap::new(),
+      enclosing: Some(enclosing),
+    }
+  }
+
+  // Redeclarar no mesmo escopo sobrescreve (útil no REPL)
+  pub fn define(&mut self, name: &str, value: RuntimeValue, constant: bool) {
+    self.values.insert(name.to_string(), Binding { value, constant });
+  }
+
+  pub fn get(&self, name: &Token) -> Result<RuntimeValue, ShiftError> {
+    if let Some(binding) = self.values.get(&name.lexeme) {
+      return Ok(binding.value.clone());
+    }
+
+    match &self.enclosing {
+      Some(enclosing) => enclosing.borrow().get(name),
+      None => Err(undefined(name)),
+    }
+  }
+
+  pub fn assign(&mut self, name: &Token, value: RuntimeValue) -> Result<(), ShiftError> {
+    if let Some(binding) = self.values.get_mut(&name.lexeme) {
+      if binding.constant {
+        return Err(ShiftError::runtime(
+          codes::CONSTANT_ASSIGNMENT,
+          name.span,
+          format!("Cannot assign to constant '{}'.", name.lexeme),
+        ).with_help(format!("declare '{}' with 'let' if it needs to change", name.lexeme)));
+      }
+
+      binding.value = value;
+      return Ok(());
+    }
+
+    match &self.enclosing {
+      Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+      None => Err(undefined(name)),
+    }
+  }
+}
+
+fn undefined(name: &Token) -> ShiftError {
+  ShiftError::runtime(codes::UNDEFINED_VARIABLE, name.span, format!("Undefined variable '{}'.", name.lexeme))
+}
@@ -1,29 +1,141 @@
+use std::fmt;
 
-use std::cell::Cell;
+use crate::token::Span;
 
-thread_local! {
-    static HAD_ERROR: Cell<bool> = Cell::new(false);
+// Em qual etapa do pipeline o erro aconteceu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+  Lex,
+  Parse,
+  Runtime,
+  Codegen,
+  // Aviso do `lint`, o script roda mesmo assim
+  Warning,
 }
 
+// Códigos estáveis, para quem embute o shift poder filtrar diagnósticos
+// sem depender do texto da mensagem.
+pub mod codes {
+  // Scanner
+  pub const UNEXPECTED_CHARACTER: &str = "E101";
+  pub const UNTERMINATED_STRING: &str = "E102";
+  pub const UNTERMINATED_COMMENT: &str = "E103";
+  pub const INVALID_ESCAPE: &str = "E104";
+  pub const INVALID_NUMBER: &str = "E105";
+  pub const NUMBER_OUT_OF_RANGE: &str = "E106";
 
-// Captura o erro
-pub fn had_error() -> bool {
-    HAD_ERROR.with(|had_error| had_error.get())
+  // Parser
+  pub const EXPECTED_TOKEN: &str = "E201";
+  pub const EXPECTED_EXPRESSION: &str = "E202";
+  pub const INVALID_ASSIGNMENT_TARGET: &str = "E203";
+  pub const OUTSIDE_LOOP: &str = "E204";
+  pub const OUTSIDE_FUNCTION: &str = "E205";
+
+  // Interpretador
+  pub const TYPE_MISMATCH: &str = "E301";
+  pub const UNDEFINED_VARIABLE: &str = "E302";
+  pub const CONSTANT_ASSIGNMENT: &str = "E303";
+  pub const DIVISION_BY_ZERO: &str = "E304";
+  pub const NOT_CALLABLE: &str = "E305";
+  pub const ARITY_MISMATCH: &str = "E306";
+  pub const NATIVE_ERROR: &str = "E307";
+  pub const INTEGER_OVERFLOW: &str = "E308";
+  pub const INDEX_OUT_OF_BOUNDS: &str = "E309";
+  pub const UNDEFINED_PROPERTY: &str = "E310";
+  pub const NO_MATCH: &str = "E311";
+  pub const TRAIT_MISMATCH: &str = "E312";
+
+  // Transpilador
+  pub const UNSUPPORTED: &str = "E401";
+  pub const MISSING_ANNOTATION: &str = "E402";
+  pub const TYPE_ERROR: &str = "E403";
+
+  // Avisos
+  pub const NON_EXHAUSTIVE_MATCH: &str = "W501";
+}
+
+// Informação extra mostrada junto com o erro
+#[derive(Debug, Clone, PartialEq)]
+pub enum Note {
+  // Outro trecho do código destacado, com uma explicação curta
+  Label { span: Span, message: String },
+  // Sugestão de como corrigir, mostrada como "help:"
+  Help(String),
+}
+
+// Erro de qualquer etapa do pipeline. É devolvido como valor, então quem
+// embute o shift coleta os diagnósticos sem precisar ler o stderr.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShiftError {
+  pub kind: ErrorKind,
+  pub code: &'static str,
+  pub message: String,
+  pub span: Option<Span>,
+  pub notes: Vec<Note>,
 }
 
-// Reseta o erro
-pub fn reset_error() {
-    HAD_ERROR.with(|had_error| had_error.set(false));
+impl ShiftError {
+  pub fn new(kind: ErrorKind, code: &'static str, span: Option<Span>, message: impl Into<String>) -> Self {
+    Self {
+      kind,
+      code,
+      message: message.into(),
+      span,
+      notes: Vec::new(),
+    }
+  }
+
+  pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+    self.notes.push(Note::Label { span, message: message.into() });
+    self
+  }
+
+  pub fn with_help(mut self, help: impl Into<String>) -> Self {
+    self.notes.push(Note::Help(help.into()));
+    self
+  }
+
+  pub fn lex(code: &'static str, span: Span, message: impl Into<String>) -> Self {
+    Self::new(ErrorKind::Lex, code, Some(span), message)
+  }
+
+  pub fn parse(code: &'static str, span: Span, message: impl Into<String>) -> Self {
+    Self::new(ErrorKind::Parse, code, Some(span), message)
+  }
+
+  pub fn runtime(code: &'static str, span: Span, message: impl Into<String>) -> Self {
+    Self::new(ErrorKind::Runtime, code, Some(span), message)
+  }
+
+  pub fn codegen(code: &'static str, span: Span, message: impl Into<String>) -> Self {
+    Self::new(ErrorKind::Codegen, code, Some(span), message)
+  }
+
+  pub fn warning(code: &'static str, span: Span, message: impl Into<String>) -> Self {
+    Self::new(ErrorKind::Warning, code, Some(span), message)
+  }
 }
 
-// Chama o erro e usa o report para printar o erro no terminal
-pub fn error(line: usize, message: &str) {
-    report(line, "", message);
+impl fmt::Display for ErrorKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ErrorKind::Lex => write!(f, "Syntax Error"),
+      ErrorKind::Parse => write!(f, "Parse Error"),
+      ErrorKind::Runtime => write!(f, "Runtime Error"),
+      ErrorKind::Codegen => write!(f, "Build Error"),
+      ErrorKind::Warning => write!(f, "Warning"),
+    }
+  }
 }
 
+impl fmt::Display for ShiftError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if let Some(span) = self.span {
+      write!(f, "[Line {}:{}] ", span.line, span.column)?;
+    }
+
+    write!(f, "{} [{}]: {}", self.kind, self.code, self.message)
+  }
+}
 
-//Printa o erro
-fn report(line: usize, locate: &str, message: &str){
-    eprintln!("[line: {}] Error {}: {}", line, locate, message);
-    HAD_ERROR.with(|had_error| had_error.set(true))
-}
\ No newline at end of file
+impl std::error::Error for ShiftError {}
@@ -1,5 +1,6 @@
-use crate::token::{Token, TokenType};
+use crate::token::{Span, Token};
 use std::fmt;
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub enum Expr {
@@ -7,19 +8,410 @@ pub enum Expr {
     left: Box<Expr>,
     operator: Token,
     right: Box<Expr>,
+    span: Span,
   },
 
   Grouping {
-    expression: Box<Expr>
+    expression: Box<Expr>,
+    span: Span,
+  },
+
+  // &&, || e ?? ficam fora de Binary porque avaliam o lado direito sob demanda
+  Logical {
+    left: Box<Expr>,
+    operator: Token,
+    right: Box<Expr>,
+    span: Span,
+  },
+
+  // cond ? a : b
+  Ternary {
+    condition: Box<Expr>,
+    then_branch: Box<Expr>,
+    else_branch: Box<Expr>,
+    span: Span,
   },
 
   Literal {
     value: LiteralValue,
+    span: Span,
   },
 
   Unary {
     operator: Token,
     right: Box<Expr>,
+    span: Span,
+  },
+
+  Variable {
+    name: Token,
+    span: Span,
+  },
+
+  Assign {
+    name: Token,
+    value: Box<Expr>,
+    span: Span,
+  },
+
+  // x += 1: o alvo é avaliado uma vez só
+  CompoundAssign {
+    target: Box<Expr>,
+    operator: Token,
+    value: Box<Expr>,
+    span: Span,
+  },
+
+  // ++x, x++, --x, x--: `prefix` decide se devolve o valor novo ou o antigo
+  Update {
+    target: Box<Expr>,
+    operator: Token,
+    prefix: bool,
+    span: Span,
+  },
+
+  Call {
+    callee: Box<Expr>,
+    paren: Token,
+    arguments: Vec<Expr>,
+    span: Span,
+  },
+
+  // [1, 2, ...outros]
+  Array {
+    elements: Vec<Expr>,
+    span: Span,
+  },
+
+  // { chave: valor, atalho, ...outro }
+  Object {
+    properties: Vec<Property>,
+    span: Span,
+  },
+
+  // User { name: "Ana", age: 30 } ou Shape.Rect { w: 1, h: 2 }
+  StructLiteral {
+    name: Token,
+    variant: Option<Token>,
+    properties: Vec<Property>,
+    span: Span,
+  },
+
+  // match valor { padrão if guarda => expressão, ... }
+  Match {
+    subject: Box<Expr>,
+    arms: Vec<MatchArm>,
+    span: Span,
+  },
+
+  // obj.campo = valor
+  Set {
+    object: Box<Expr>,
+    name: Token,
+    value: Box<Expr>,
+    span: Span,
+  },
+
+  // `...lista` dentro de um literal de array
+  Spread {
+    expression: Box<Expr>,
+    span: Span,
+  },
+
+  // lista[i], com i negativo contando do fim
+  Index {
+    object: Box<Expr>,
+    index: Box<Expr>,
+    span: Span,
+  },
+
+  SetIndex {
+    object: Box<Expr>,
+    index: Box<Expr>,
+    value: Box<Expr>,
+    span: Span,
+  },
+
+  // obj.campo, ou obj?.campo que vira null quando obj é null
+  Get {
+    object: Box<Expr>,
+    name: Token,
+    optional: bool,
+    span: Span,
+  },
+
+  // "texto ${expr} texto": literais e expressões intercalados, na ordem
+  Template {
+    parts: Vec<Expr>,
+    span: Span,
+  },
+
+  // (a, b) => { ... } ou (a) => a * 2
+  Closure {
+    declaration: Rc<FunctionDecl>,
+    span: Span,
+  },
+}
+
+impl Expr {
+  // Trecho do código que originou a expressão inteira
+  pub fn span(&self) -> Span {
+    match self {
+      Expr::Binary { span, .. }
+      | Expr::Grouping { span, .. }
+      | Expr::Logical { span, .. }
+      | Expr::Ternary { span, .. }
+      | Expr::Literal { span, .. }
+      | Expr::Unary { span, .. }
+      | Expr::Variable { span, .. }
+      | Expr::Assign { span, .. }
+      | Expr::CompoundAssign { span, .. }
+      | Expr::Update { span, .. }
+      | Expr::Call { span, .. }
+      | Expr::Array { span, .. }
+      | Expr::Spread { span, .. }
+      | Expr::Object { span, .. }
+      | Expr::StructLiteral { span, .. }
+      | Expr::Match { span, .. }
+      | Expr::Set { span, .. }
+      | Expr::Index { span, .. }
+      | Expr::SetIndex { span, .. }
+      | Expr::Get { span, .. }
+      | Expr::Template { span, .. }
+      | Expr::Closure { span, .. } => *span,
+    }
+  }
+
+  // Subexpressões diretas, sem entrar no corpo de closures.
+  // Em `match` entram o valor, as guardas e os corpos dos braços.
+  pub fn children(&self) -> Vec<&Expr> {
+    match self {
+      Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => vec![left, right],
+      Expr::Ternary { condition, then_branch, else_branch, .. } => vec![condition, then_branch, else_branch],
+      Expr::Grouping { expression, .. } | Expr::Spread { expression, .. } => vec![expression],
+      Expr::Unary { right, .. } => vec![right],
+      Expr::Assign { value, .. } => vec![value],
+      Expr::CompoundAssign { target, value, .. } => vec![target, value],
+      Expr::Update { target, .. } => vec![target],
+      Expr::Call { callee, arguments, .. } => std::iter::once(callee.as_ref()).chain(arguments).collect(),
+      Expr::Array { elements, .. } => elements.iter().collect(),
+      Expr::Template { parts, .. } => parts.iter().collect(),
+      Expr::Index { object, index, .. } => vec![object, index],
+      Expr::SetIndex { object, index, value, .. } => vec![object, index, value],
+      Expr::Get { object, .. } => vec![object],
+      Expr::Set { object, value, .. } => vec![object, value],
+      Expr::Object { properties, .. } | Expr::StructLiteral { properties, .. } => properties.iter().map(|property| match property {
+        Property::Field { value, .. } => value,
+        Property::Spread(expression) => expression,
+      }).collect(),
+      Expr::Match { subject, arms, .. } => {
+        let mut children = vec![subject.as_ref()];
+        for arm in arms {
+          children.extend(&arm.guard);
+          if let Stmt::Expression { expression } = &arm.body {
+            children.push(expression);
+          }
+        }
+        children
+      },
+      Expr::Literal { .. } | Expr::Variable { .. } | Expr::Closure { .. } => Vec::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum Property {
+  // `chave: valor`, ou só `chave` quando o valor é a variável de mesmo nome
+  Field { key: String, key_span: Span, value: Expr },
+  Spread(Expr),
+}
+
+// Variante de `enum Nome { ... }`
+#[derive(Debug, Clone)]
+pub struct Variant {
+  pub name: Token,
+  pub fields: VariantFields,
+}
+
+#[derive(Debug, Clone)]
+pub enum VariantFields {
+  // Empty
+  Unit,
+  // Circle(float), só com os tipos
+  Tuple(Vec<Token>),
+  // Rect { w: float, h: float }
+  Named(Vec<Field>),
+}
+
+// Um braço de `match`. Em `Expr::Match` o corpo é sempre `Stmt::Expression`;
+// só o `match` usado como instrução aceita blocos.
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+  pub pattern: Pattern,
+  pub guard: Option<Expr>,
+  pub body: Stmt,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+  // _
+  Wildcard(Span),
+  // x, que recebe o valor
+  Binding(Token),
+  Literal { value: LiteralValue, span: Span },
+  // 1..10, sem o fim, como no `for`
+  Range { start: LiteralValue, end: LiteralValue, span: Span },
+  // Shape.Circle(r), Shape.Rect { w, h: 0 }, Shape.Empty
+  Variant { enum_name: Token, variant: Token, fields: FieldPatterns, span: Span },
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldPatterns {
+  Unit,
+  Tuple(Vec<Pattern>),
+  Named(Vec<(Token, Pattern)>),
+}
+
+impl Pattern {
+  pub fn span(&self) -> Span {
+    match self {
+      Pattern::Wildcard(span) => *span,
+      Pattern::Binding(name) => name.span,
+      Pattern::Literal { span, .. } | Pattern::Range { span, .. } | Pattern::Variant { span, .. } => *span,
+    }
+  }
+
+  // Casa com qualquer valor: `_` e `x`
+  pub fn is_irrefutable(&self) -> bool {
+    matches!(self, Pattern::Wildcard(_) | Pattern::Binding(_))
+  }
+}
+
+// Campo declarado em `struct Nome { campo: tipo }`
+#[derive(Debug, Clone)]
+pub struct Field {
+  pub name: Token,
+  pub type_annotation: Option<Token>,
+}
+
+// Método de um bloco `impl`. O `self` não fica em `params`:
+// `has_self` diz se ele era o primeiro parâmetro.
+#[derive(Debug, Clone)]
+pub struct Method {
+  pub declaration: Rc<FunctionDecl>,
+  pub has_self: bool,
+}
+
+// Método declarado num `trait`. Sem corpo padrão, `body` fica vazio
+// e todo `impl` do trait precisa defini-lo.
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+  pub method: Method,
+  pub has_default: bool,
+}
+
+// Compartilhado entre `fn nome() {}` e closures, o corpo não é copiado
+// a cada vez que a função é criada em tempo de execução.
+#[derive(Debug, Clone)]
+pub struct FunctionDecl {
+  pub name: Option<Token>,
+  pub params: Vec<Param>,
+  pub return_type: Option<Token>,
+  pub body: Vec<Stmt>,
+}
+
+// Anotações de tipo são opcionais no interpretador, mas o
+// transpilador precisa delas nas assinaturas de funções.
+#[derive(Debug, Clone)]
+pub struct Param {
+  pub name: Token,
+  pub type_annotation: Option<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+  Expression {
+    expression: Expr,
+  },
+
+  Let {
+    name: Token,
+    type_annotation: Option<Token>,
+    initializer: Option<Expr>,
+  },
+
+  Const {
+    name: Token,
+    type_annotation: Option<Token>,
+    initializer: Expr,
+  },
+
+  Block {
+    statements: Vec<Stmt>,
+  },
+
+  If {
+    condition: Expr,
+    then_branch: Box<Stmt>,
+    else_branch: Option<Box<Stmt>>,
+  },
+
+  While {
+    condition: Expr,
+    body: Box<Stmt>,
+  },
+
+  Loop {
+    body: Box<Stmt>,
+  },
+
+  // for variable in start..end
+  For {
+    variable: Token,
+    start: Expr,
+    end: Expr,
+    body: Box<Stmt>,
+  },
+
+  Break,
+
+  Continue,
+
+  Function {
+    declaration: Rc<FunctionDecl>,
+  },
+
+  Return {
+    value: Option<Expr>,
+  },
+
+  Struct {
+    name: Token,
+    fields: Vec<Field>,
+  },
+
+  // impl Nome { fn metodo(self) { ... } } ou impl Trait for Nome { ... }
+  Impl {
+    name: Token,
+    trait_name: Option<Token>,
+    methods: Vec<Method>,
+  },
+
+  // trait Nome { fn metodo(self) -> string }
+  Trait {
+    name: Token,
+    methods: Vec<TraitMethod>,
+  },
+
+  Enum {
+    name: Token,
+    variants: Vec<Variant>,
+  },
+
+  // `match` no início da instrução, com braços que podem ser blocos
+  Match {
+    subject: Expr,
+    arms: Vec<MatchArm>,
   },
 }
 
@@ -35,18 +427,142 @@ pub enum LiteralValue {
 impl fmt::Display for Expr {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
-      Expr::Binary { left, operator, right } => {
+      Expr::Binary { left, operator, right, .. } => {
+        write!(f, "({} {} {})", operator.lexeme, left , right)
+      },
+      Expr::Logical { left, operator, right, .. } => {
         write!(f, "({} {} {})", operator.lexeme, left , right)
       },
-      Expr::Grouping { expression } => {
+      Expr::Grouping { expression, .. } => {
           write!(f, "(group {})", expression)
       },
-      Expr::Literal { value } => {
+      Expr::Ternary { condition, then_branch, else_branch, .. } => {
+          write!(f, "(? {} {} {})", condition, then_branch, else_branch)
+      },
+      Expr::Array { elements, .. } => {
+          write!(f, "(array")?;
+          for element in elements {
+              write!(f, " {}", element)?;
+          }
+          write!(f, ")")
+      },
+      Expr::Object { properties, .. } => {
+          write!(f, "(object")?;
+          for property in properties {
+              match property {
+                  Property::Field { key, value, .. } => write!(f, " ({} {})", key, value)?,
+                  Property::Spread(expression) => write!(f, " (... {})", expression)?,
+              }
+          }
+          write!(f, ")")
+      },
+      Expr::StructLiteral { name, variant, properties, .. } => {
+          write!(f, "(struct {}", name.lexeme)?;
+          if let Some(variant) = variant {
+              write!(f, ".{}", variant.lexeme)?;
+          }
+          for property in properties {
+              match property {
+                  Property::Field { key, value, .. } => write!(f, " ({} {})", key, value)?,
+                  Property::Spread(expression) => write!(f, " (... {})", expression)?,
+              }
+          }
+          write!(f, ")")
+      },
+      Expr::Match { subject, arms, .. } => {
+          write!(f, "(match {}", subject)?;
+          for arm in arms {
+              write!(f, " ({}", arm.pattern)?;
+              if let Some(guard) = &arm.guard {
+                  write!(f, " if {}", guard)?;
+              }
+              match &arm.body {
+                  Stmt::Expression { expression } => write!(f, " => {})", expression)?,
+                  _ => write!(f, " => {{...}})")?,
+              }
+          }
+          write!(f, ")")
+      },
+      Expr::Set { object, name, value, .. } => {
+          write!(f, "(.= {} {} {})", object, name.lexeme, value)
+      },
+      Expr::Spread { expression, .. } => {
+          write!(f, "(... {})", expression)
+      },
+      Expr::Index { object, index, .. } => {
+          write!(f, "([] {} {})", object, index)
+      },
+      Expr::SetIndex { object, index, value, .. } => {
+          write!(f, "([]= {} {} {})", object, index, value)
+      },
+      Expr::Get { object, name, optional, .. } => {
+          write!(f, "({} {} {})", if *optional { "?." } else { "." }, object, name.lexeme)
+      },
+      Expr::Literal { value, .. } => {
           write!(f, "{}", value)
       },
-      Expr::Unary { operator, right } => {
+      Expr::Unary { operator, right, .. } => {
           write!(f, "({} {})", operator.lexeme, right)
       },
+      Expr::Variable { name, .. } => {
+          write!(f, "{}", name.lexeme)
+      },
+      Expr::Assign { name, value, .. } => {
+          write!(f, "(= {} {})", name.lexeme, value)
+      },
+      Expr::CompoundAssign { target, operator, value, .. } => {
+          write!(f, "({} {} {})", operator.lexeme, target, value)
+      },
+      Expr::Update { target, operator, prefix, .. } => {
+          if *prefix {
+              write!(f, "(prefix{} {})", operator.lexeme, target)
+          } else {
+              write!(f, "(postfix{} {})", operator.lexeme, target)
+          }
+      },
+      Expr::Call { callee, arguments, .. } => {
+          write!(f, "(call {}", callee)?;
+          for argument in arguments {
+              write!(f, " {}", argument)?;
+          }
+          write!(f, ")")
+      },
+      Expr::Template { parts, .. } => {
+          write!(f, "(template")?;
+          for part in parts {
+              write!(f, " {}", part)?;
+          }
+          write!(f, ")")
+      },
+      Expr::Closure { declaration, .. } => {
+          let params: Vec<&str> = declaration.params.iter().map(|p| p.name.lexeme.as_str()).collect();
+          write!(f, "(closure ({}))", params.join(" "))
+      },
+    }
+  }
+}
+
+impl fmt::Display for Pattern {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Pattern::Wildcard(_) => write!(f, "_"),
+      Pattern::Binding(name) => write!(f, "{}", name.lexeme),
+      Pattern::Literal { value, .. } => write!(f, "{}", value),
+      Pattern::Range { start, end, .. } => write!(f, "{}..{}", start, end),
+      Pattern::Variant { enum_name, variant, fields, .. } => {
+        write!(f, "{}.{}", enum_name.lexeme, variant.lexeme)?;
+        match fields {
+          FieldPatterns::Unit => Ok(()),
+          FieldPatterns::Tuple(patterns) => {
+            let patterns: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+            write!(f, "({})", patterns.join(", "))
+          },
+          FieldPatterns::Named(fields) => {
+            let fields: Vec<String> = fields.iter().map(|(name, pattern)| format!("{}: {}", name.lexeme, pattern)).collect();
+            write!(f, " {{ {} }}", fields.join(", "))
+          },
+        }
+      },
     }
   }
 }
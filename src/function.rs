@@ -0,0 +1,115 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::environment::Environment;
+use crate::errors::{codes, ErrorKind, ShiftError};
+use crate::expr::FunctionDecl;
+use crate::interpreter::{Interpreter, RuntimeValue, Unwind};
+
+// Interface comum de tudo que pode ser chamado com `f(...)`
+pub trait Callable {
+  fn arity(&self) -> usize;
+  fn call(&self, interpreter: &mut Interpreter, arguments: Vec<RuntimeValue>) -> Result<RuntimeValue, ShiftError>;
+}
+
+// Função definida no script, junto com o escopo onde foi criada
+pub struct Function {
+  declaration: Rc<FunctionDecl>,
+  closure: Rc<RefCell<Environment>>,
+}
+
+impl Function {
+  pub(crate) fn new(declaration: Rc<FunctionDecl>, closure: Rc<RefCell<Environment>>) -> Self {
+    Self { declaration, closure }
+  }
+
+  // Cópia do método com `self` definido num escopo logo acima do corpo
+  pub(crate) fn bind(&self, instance: RuntimeValue) -> Self {
+    let mut environment = Environment::new_enclosed(Rc::clone(&self.closure));
+    environment.define("self", instance, true);
+
+    Self::new(Rc::clone(&self.declaration), Rc::new(RefCell::new(environment)))
+  }
+}
+
+impl Callable for Function {
+  fn arity(&self) -> usize {
+    self.declaration.params.len()
+  }
+
+  fn call(&self, interpreter: &mut Interpreter, arguments: Vec<RuntimeValue>) -> Result<RuntimeValue, ShiftError> {
+    let mut environment = Environment::new_enclosed(Rc::clone(&self.closure));
+
+    for (param, argument) in self.declaration.params.iter().zip(arguments) {
+      environment.define(&param.name.lexeme, argument, false);
+    }
+
+    match interpreter.execute_block(&self.declaration.body, Rc::new(RefCell::new(environment))) {
+      Ok(()) => Ok(RuntimeValue::Null),
+      Err(Unwind::Return(value)) => Ok(value),
+      Err(unwind) => Err(unwind.into_error()),
+    }
+  }
+}
+
+// O escopo capturado pode conter a própria função, então não entra no Debug
+impl fmt::Debug for Function {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self)
+  }
+}
+
+impl fmt::Display for Function {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match &self.declaration.name {
+      Some(name) => write!(f, "<fn {}>", name.lexeme),
+      None => write!(f, "<closure>"),
+    }
+  }
+}
+
+// Funções nativas devolvem só a mensagem de erro
+pub type NativeFn = dyn Fn(&[RuntimeValue]) -> Result<RuntimeValue, String>;
+
+// Função implementada em Rust e exposta para os scripts
+pub struct NativeFunction {
+  name: String,
+  arity: usize,
+  function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+  pub fn new(name: &str, arity: usize, function: Box<NativeFn>) -> Self {
+    Self {
+      name: name.to_string(),
+      arity,
+      function,
+    }
+  }
+}
+
+impl Callable for NativeFunction {
+  fn arity(&self) -> usize {
+    self.arity
+  }
+
+  fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<RuntimeValue>) -> Result<RuntimeValue, ShiftError> {
+    // Sem linha: o interpretador preenche com a linha da chamada
+    (self.function)(&arguments).map_err(|message| {
+      ShiftError::new(ErrorKind::Runtime, codes::NATIVE_ERROR, None, message)
+    })
+  }
+}
+
+impl fmt::Debug for NativeFunction {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self)
+  }
+}
+
+impl fmt::Display for NativeFunction {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<native fn {}>", self.name)
+  }
+}
@@ -1,17 +1,61 @@
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
 
-use crate::expr::{Expr, LiteralValue};
-use crate::token::{Token, TokenType};
+use crate::environment::Environment;
+use crate::errors::{codes, ErrorKind, ShiftError};
+use crate::enums::{EnumDef, EnumValue, Payload, VariantDef, VariantKind};
+use crate::expr::{Expr, FieldPatterns, LiteralValue, MatchArm, Method, Pattern, Property, Stmt, VariantFields};
+use crate::token::{Span, Token};
+use crate::function::{Callable, Function, NativeFunction};
+use crate::natives;
+use crate::object::Object;
+use crate::structs::{Instance, StructDef, StructMethod};
+use crate::token::TokenType;
+use crate::traits::{TraitDef, TraitMethodDef};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum RuntimeValue {
   Integer(i64),
   Float(f64),
   String(String),
   Boolean(bool),
+  Function(Rc<Function>),
+  NativeFunction(Rc<NativeFunction>),
+  // Compartilhado entre variáveis: `let b = a` não copia a lista
+  Array(Rc<RefCell<Vec<RuntimeValue>>>),
+  Object(Rc<RefCell<Object>>),
+  Struct(Rc<StructDef>),
+  Instance(Rc<Instance>),
+  Enum(Rc<EnumDef>),
+  Variant(Rc<EnumValue>),
+  Trait(Rc<TraitDef>),
   Null,
 }
 
+impl PartialEq for RuntimeValue {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => l == r,
+      (RuntimeValue::Float(l), RuntimeValue::Float(r)) => l == r,
+      (RuntimeValue::String(l), RuntimeValue::String(r)) => l == r,
+      (RuntimeValue::Boolean(l), RuntimeValue::Boolean(r)) => l == r,
+      // Funções só são iguais a elas mesmas
+      (RuntimeValue::Function(l), RuntimeValue::Function(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::NativeFunction(l), RuntimeValue::NativeFunction(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Array(l), RuntimeValue::Array(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Object(l), RuntimeValue::Object(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Struct(l), RuntimeValue::Struct(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Instance(l), RuntimeValue::Instance(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Enum(l), RuntimeValue::Enum(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Variant(l), RuntimeValue::Variant(r)) => l == r,
+      (RuntimeValue::Trait(l), RuntimeValue::Trait(r)) => Rc::ptr_eq(l, r),
+      (RuntimeValue::Null, RuntimeValue::Null) => true,
+      _ => false,
+    }
+  }
+}
+
 impl fmt::Display for RuntimeValue {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
@@ -19,145 +63,1260 @@ impl fmt::Display for RuntimeValue {
       RuntimeValue::Float(fl) => write!(f, "{}", fl),
       RuntimeValue::String(s) => write!(f, "{}", s),
       RuntimeValue::Boolean(b) => write!(f, "{}", b),
+      RuntimeValue::Function(function) => write!(f, "{}", function),
+      RuntimeValue::NativeFunction(function) => write!(f, "{}", function),
+      RuntimeValue::Array(items) => display_once(f, Rc::as_ptr(items).cast(), "[...]", |f| {
+        write!(f, "[")?;
+        for (i, item) in items.borrow().iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          // Dentro de listas as strings aparecem com aspas
+          match item {
+            RuntimeValue::String(s) => write!(f, "{:?}", s)?,
+            item => write!(f, "{}", item)?,
+          }
+        }
+        write!(f, "]")
+      }),
+      RuntimeValue::Object(object) => write!(f, "{}", object.borrow()),
+      RuntimeValue::Struct(def) => write!(f, "{}", def),
+      RuntimeValue::Instance(instance) => write!(f, "{}", instance),
+      RuntimeValue::Enum(def) => write!(f, "{}", def),
+      RuntimeValue::Variant(value) => write!(f, "{}", value),
+      RuntimeValue::Trait(def) => write!(f, "{}", def),
       RuntimeValue::Null => write!(f, "null"),
     }
   }
 }
 
-pub struct Interpreter;
+thread_local! {
+  // Listas e objetos sendo mostrados agora. Como são compartilhados,
+  // `a[0] = a` cria um ciclo que o Display seguiria para sempre.
+  static SHOWING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
+
+// Mostra `contents`, ou `cycle` se o valor já está sendo mostrado mais acima
+pub(crate) fn display_once(
+  f: &mut fmt::Formatter,
+  value: *const (),
+  cycle: &str,
+  contents: impl FnOnce(&mut fmt::Formatter) -> fmt::Result,
+) -> fmt::Result {
+  if SHOWING.with_borrow(|showing| showing.contains(&value)) {
+    return write!(f, "{}", cycle);
+  }
+
+  SHOWING.with_borrow_mut(|showing| showing.push(value));
+  let result = contents(f);
+  SHOWING.with_borrow_mut(|showing| showing.pop());
+  result
+}
+
+// Sinais que interrompem a execução normal das instruções.
+// `Break` e `Continue` sobem até o loop mais próximo, `Return` até a chamada
+// da função e `Error` até o `interpret`.
+#[derive(Debug)]
+pub(crate) enum Unwind {
+  Break,
+  Continue,
+  Return(RuntimeValue),
+  Error(ShiftError),
+}
+
+impl From<ShiftError> for Unwind {
+  fn from(error: ShiftError) -> Self {
+    Unwind::Error(error)
+  }
+}
+
+impl Unwind {
+  // O parser não deixa break/continue saírem de um loop nem return sair
+  // de uma função, mas uma AST montada à mão pode deixar. Sem o token
+  // da instrução, o erro fica sem posição.
+  pub(crate) fn into_error(self) -> ShiftError {
+    match self {
+      Unwind::Error(error) => error,
+      Unwind::Break => ShiftError::new(ErrorKind::Runtime, codes::OUTSIDE_LOOP, None, "'break' outside of a loop."),
+      Unwind::Continue => ShiftError::new(ErrorKind::Runtime, codes::OUTSIDE_LOOP, None, "'continue' outside of a loop."),
+      Unwind::Return(_) => ShiftError::new(ErrorKind::Runtime, codes::OUTSIDE_FUNCTION, None, "'return' outside of a function."),
+    }
+  }
+}
+
+// Onde uma atribuição grava, já com o objeto e o índice avaliados,
+// para que `lista[f()] += 1` chame `f` uma vez só. O elemento guarda o
+// span do índice, já que o array pode encolher antes da gravação.
+enum Place {
+  Variable(Token),
+  Element(Rc<RefCell<Vec<RuntimeValue>>>, usize, Span),
+  Property(Rc<RefCell<Object>>, String),
+}
+
+pub struct Interpreter {
+  globals: Rc<RefCell<Environment>>,
+  environment: Rc<RefCell<Environment>>,
+}
+
+impl Default for Interpreter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
 
 impl Interpreter {
-  pub fn interpret(&self, expression: &Expr) {
-    match self.evaluate(expression) {
-      Ok(value) => { println!("{}", value);},
+  pub fn new() -> Self {
+    let globals = Rc::new(RefCell::new(Environment::new()));
 
-      Err(error) => {
-        eprintln!("Runtime Error: {}", error);
-      }
+    let mut interpreter = Self {
+      environment: Rc::clone(&globals),
+      globals,
+    };
+    natives::register(&mut interpreter);
+
+    interpreter
+  }
+
+  // Registra uma função Rust no escopo global:
+  // `interpreter.define_native("now", 0, |args| ...)`
+  pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+  where
+    F: Fn(&[RuntimeValue]) -> Result<RuntimeValue, String> + 'static,
+  {
+    let native = NativeFunction::new(name, arity, Box::new(function));
+    self.globals.borrow_mut().define(name, RuntimeValue::NativeFunction(Rc::new(native)), false);
+  }
+
+  // Executa o programa e devolve o valor da última instrução, se ela
+  // for uma expressão (é o que o REPL mostra)
+  pub fn interpret(&mut self, statements: &[Stmt]) -> Result<RuntimeValue, ShiftError> {
+    self.hoist(statements)?;
+    let mut last = RuntimeValue::Null;
+
+    for statement in statements {
+      let result = match statement {
+        Stmt::Expression { expression } => self.evaluate(expression).map(|value| last = value),
+        statement if is_item(statement) => {
+          last = RuntimeValue::Null;
+          Ok(())
+        },
+        statement => {
+          last = RuntimeValue::Null;
+          self.execute(statement).map_err(Unwind::into_error)
+        },
+      };
+
+      result?;
     }
+
+    Ok(last)
   }
 
-  fn evaluate(&self, expr: &Expr) -> Result<RuntimeValue, String> {
-    match expr {
-      //Literals
-      Expr::Literal { value } => {
-        let runtime_val = match value {
-          LiteralValue::Integer(i) => RuntimeValue::Integer(*i),
-          LiteralValue::Float(f) => RuntimeValue::Float(*f),
-          LiteralValue::String(s) => RuntimeValue::String(s.clone()),
-          LiteralValue::Boolean(b) => RuntimeValue::Boolean(*b),
-          LiteralValue::Null => RuntimeValue::Null,
+  fn execute(&mut self, stmt: &Stmt) -> Result<(), Unwind> {
+    match stmt {
+      Stmt::Expression { expression } => {
+        self.evaluate(expression)?;
+        Ok(())
+      },
+
+      Stmt::Let { name, initializer, .. } => {
+        let value = match initializer {
+          Some(expr) => self.evaluate(expr)?,
+          None => RuntimeValue::Null,
+        };
+
+        self.environment.borrow_mut().define(&name.lexeme, value, false);
+        Ok(())
+      },
+
+      Stmt::Const { name, initializer, .. } => {
+        let value = self.evaluate(initializer)?;
+        self.environment.borrow_mut().define(&name.lexeme, value, true);
+        Ok(())
+      },
+
+      Stmt::Block { statements } => {
+        let environment = Environment::new_enclosed(Rc::clone(&self.environment));
+        self.execute_block(statements, Rc::new(RefCell::new(environment)))
+      },
+
+      Stmt::If { condition, then_branch, else_branch } => {
+        let condition = self.evaluate(condition)?;
+
+        if self.is_truthy(&condition) {
+          self.execute(then_branch)
+        } else if let Some(else_branch) = else_branch {
+          self.execute(else_branch)
+        } else {
+          Ok(())
+        }
+      },
+
+      Stmt::While { condition, body } => {
+        loop {
+          let value = self.evaluate(condition)?;
+          if !self.is_truthy(&value) {
+            break;
+          }
+
+          match self.execute(body) {
+            Ok(()) | Err(Unwind::Continue) => {},
+            Err(Unwind::Break) => break,
+            Err(error) => return Err(error),
+          }
+        }
+
+        Ok(())
+      },
+
+      Stmt::Loop { body } => {
+        loop {
+          match self.execute(body) {
+            Ok(()) | Err(Unwind::Continue) => {},
+            Err(Unwind::Break) => break,
+            Err(error) => return Err(error),
+          }
+        }
+
+        Ok(())
+      },
+
+      Stmt::For { variable, start, end, body } => {
+        let (start, end) = match (self.evaluate(start)?, self.evaluate(end)?) {
+          (RuntimeValue::Integer(start), RuntimeValue::Integer(end)) => (start, end),
+          _ => return Err(ShiftError::runtime(codes::TYPE_MISMATCH, variable.span, "Range bounds must be integers.").into()),
+        };
+
+        for i in start..end {
+          // Cada iteração tem seu próprio escopo com a variável do loop
+          let mut environment = Environment::new_enclosed(Rc::clone(&self.environment));
+          environment.define(&variable.lexeme, RuntimeValue::Integer(i), false);
+
+          match self.execute_block(std::slice::from_ref(body.as_ref()), Rc::new(RefCell::new(environment))) {
+            Ok(()) | Err(Unwind::Continue) => {},
+            Err(Unwind::Break) => break,
+            Err(error) => return Err(error),
+          }
+        }
+
+        Ok(())
+      },
+
+      Stmt::Break => Err(Unwind::Break),
+
+      Stmt::Continue => Err(Unwind::Continue),
+
+      Stmt::Function { declaration } => {
+        let function = Function::new(Rc::clone(declaration), Rc::clone(&self.environment));
+        let name = declaration.name.as_ref().map_or("", |name| name.lexeme.as_str());
+
+        self.environment.borrow_mut().define(name, RuntimeValue::Function(Rc::new(function)), false);
+        Ok(())
+      },
+
+      Stmt::Return { value } => {
+        let value = match value {
+          Some(expr) => self.evaluate(expr)?,
+          None => RuntimeValue::Null,
+        };
+
+        Err(Unwind::Return(value))
+      },
+
+      Stmt::Struct { name, fields } => {
+        let fields = fields.iter().map(|field| field.name.lexeme.clone()).collect();
+        let def = StructDef::new(&name.lexeme, fields);
+
+        self.environment.borrow_mut().define(&name.lexeme, RuntimeValue::Struct(Rc::new(def)), false);
+        Ok(())
+      },
+
+      Stmt::Impl { name, trait_name, methods } => {
+        let def = match self.environment.borrow().get(name)? {
+          RuntimeValue::Struct(def) => def,
+          value => return Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            name.span,
+            format!("Can only implement methods for structs, got '{}'.", value),
+          ).into()),
         };
 
-        Ok(runtime_val)
+        if let Some(trait_name) = trait_name {
+          self.implement_trait(&def, name, trait_name, methods)?;
+          return Ok(());
+        }
+
+        for method in methods {
+          let function = Function::new(Rc::clone(&method.declaration), Rc::clone(&self.environment));
+          def.add_method(method_name(method), StructMethod { function: Rc::new(function), has_self: method.has_self });
+        }
+        Ok(())
+      },
+
+      Stmt::Trait { name, methods } => {
+        let methods = methods.iter().map(|trait_method| {
+          let method = &trait_method.method;
+          let default = trait_method.has_default.then(|| {
+            Rc::new(Function::new(Rc::clone(&method.declaration), Rc::clone(&self.environment)))
+          });
+
+          TraitMethodDef {
+            name: method_name(method).to_string(),
+            arity: method.declaration.params.len(),
+            has_self: method.has_self,
+            default,
+          }
+        }).collect();
+        let def = TraitDef { name: name.lexeme.clone(), methods };
+
+        self.environment.borrow_mut().define(&name.lexeme, RuntimeValue::Trait(Rc::new(def)), false);
+        Ok(())
+      },
+
+      Stmt::Enum { name, variants } => {
+        let variants = variants.iter().map(|variant| VariantDef {
+          name: variant.name.lexeme.clone(),
+          kind: match &variant.fields {
+            VariantFields::Unit => VariantKind::Unit,
+            VariantFields::Tuple(types) => VariantKind::Tuple(types.len()),
+            VariantFields::Named(fields) => VariantKind::Named(fields.iter().map(|field| field.name.lexeme.clone()).collect()),
+          },
+        }).collect();
+        let def = EnumDef { name: name.lexeme.clone(), variants };
+
+        self.environment.borrow_mut().define(&name.lexeme, RuntimeValue::Enum(Rc::new(def)), false);
+        Ok(())
       },
 
+      Stmt::Match { subject, arms } => {
+        let (arm, environment) = self.select_arm(subject, arms)?;
+        self.execute_block(std::slice::from_ref(&arm.body), environment)
+      },
+    }
+  }
+
+  // Executa o bloco no escopo recebido e sempre restaura o escopo anterior,
+  // mesmo quando uma instrução falha.
+  pub(crate) fn execute_block(&mut self, statements: &[Stmt], environment: Rc<RefCell<Environment>>) -> Result<(), Unwind> {
+    let previous = std::mem::replace(&mut self.environment, environment);
+
+    let result = statements.iter().try_for_each(|statement| self.execute(statement));
+
+    self.environment = previous;
+    result
+  }
+
+  // Como no Rust gerado, as declarações de topo valem no arquivo inteiro:
+  // uma função pode ser chamada antes do seu `fn`. Os tipos vêm antes dos
+  // `impl`, que precisam deles, e as funções por último.
+  fn hoist(&mut self, statements: &[Stmt]) -> Result<(), ShiftError> {
+    let types = statements.iter().filter(|statement| matches!(statement, Stmt::Struct { .. } | Stmt::Enum { .. } | Stmt::Trait { .. }));
+    let impls = statements.iter().filter(|statement| matches!(statement, Stmt::Impl { .. }));
+    let functions = statements.iter().filter(|statement| matches!(statement, Stmt::Function { .. }));
+
+    for statement in types.chain(impls).chain(functions) {
+      self.execute(statement).map_err(Unwind::into_error)?;
+    }
+
+    Ok(())
+  }
+
+  pub fn evaluate(&mut self, expr: &Expr) -> Result<RuntimeValue, ShiftError> {
+    match expr {
+      //Literals
+      Expr::Literal { value, .. } => Ok(literal(value)),
+
       // Grouping
 
-      Expr::Grouping { expression } => {
+      Expr::Grouping { expression, .. } => {
         self.evaluate(expression)
       },
       // unary
 
-      Expr::Unary { operator, right } => {
+      Expr::Unary { operator, right, .. } => {
         let r = self.evaluate(right)?;
 
-        self.evaluate_unary(operator.token_type.clone(), r)
+        self.evaluate_unary(operator, r)
       },
 
       // binary
 
-      Expr::Binary { left, operator, right } => {
+      Expr::Binary { left, operator, right, .. } => {
         let l = self.evaluate(left)?;
         let r = self.evaluate(right)?;
-        self.evaluate_binary(l, operator.token_type.clone(), r)
+        self.evaluate_binary(l, operator, r)
+      },
+
+      // logical
+
+      Expr::Logical { left, operator, right, .. } => {
+        let l = self.evaluate(left)?;
+
+        if operator.token_type == TokenType::QuestionQuestion {
+          return match l {
+            RuntimeValue::Null => self.evaluate(right),
+            value => Ok(value),
+          };
+        }
+
+        let l = self.is_truthy(&l);
+
+        // Curto-circuito: o lado direito só é avaliado se mudar o resultado
+        match operator.token_type {
+          TokenType::OrLogical if l => Ok(RuntimeValue::Boolean(true)),
+          TokenType::And if !l => Ok(RuntimeValue::Boolean(false)),
+          _ => {
+            let r = self.evaluate(right)?;
+            Ok(RuntimeValue::Boolean(self.is_truthy(&r)))
+          }
+        }
+      },
+
+      // calls
+
+      Expr::Ternary { condition, then_branch, else_branch, .. } => {
+        let condition = self.evaluate(condition)?;
+
+        if self.is_truthy(&condition) {
+          self.evaluate(then_branch)
+        } else {
+          self.evaluate(else_branch)
+        }
+      },
+
+      Expr::Get { .. } | Expr::Call { .. } => {
+        Ok(self.evaluate_chain(expr)?.unwrap_or(RuntimeValue::Null))
+      },
+
+      Expr::Template { parts, .. } => {
+        let mut text = String::new();
+        for part in parts {
+          text.push_str(&self.evaluate(part)?.to_string());
+        }
+
+        Ok(RuntimeValue::String(text))
+      },
+
+      Expr::Closure { declaration, .. } => {
+        let function = Function::new(Rc::clone(declaration), Rc::clone(&self.environment));
+        Ok(RuntimeValue::Function(Rc::new(function)))
+      },
+
+      // variables
+
+      Expr::Variable { name, .. } => {
+        self.environment.borrow().get(name)
+      },
+
+      Expr::Assign { name, value, .. } => {
+        let value = self.evaluate(value)?;
+        self.environment.borrow_mut().assign(name, value.clone())?;
+        Ok(value)
+      },
+
+      // arrays
+
+      Expr::Array { elements, .. } => {
+        let mut items = Vec::with_capacity(elements.len());
+
+        for element in elements {
+          match element {
+            Expr::Spread { expression, .. } => match self.evaluate(expression)? {
+              RuntimeValue::Array(other) => items.extend(other.borrow().iter().cloned()),
+              value => return Err(ShiftError::runtime(
+                codes::TYPE_MISMATCH,
+                expression.span(),
+                format!("Only arrays can be spread, got '{}'.", value),
+              )),
+            },
+            element => items.push(self.evaluate(element)?),
+          }
+        }
+
+        Ok(RuntimeValue::Array(Rc::new(RefCell::new(items))))
+      },
+
+      Expr::Spread { .. } => unreachable!("o parser só aceita '...' dentro de literais"),
+
+      // objects
+
+      Expr::Object { properties, .. } => {
+        let mut object = Object::new();
+
+        for property in properties {
+          match property {
+            Property::Field { key, value, .. } => {
+              let value = self.evaluate(value)?;
+              object.set(key, value);
+            },
+            Property::Spread(expression) => match self.evaluate(expression)? {
+              RuntimeValue::Object(other) => {
+                for (key, value) in other.borrow().iter() {
+                  object.set(key, value.clone());
+                }
+              },
+              value => return Err(ShiftError::runtime(
+                codes::TYPE_MISMATCH,
+                expression.span(),
+                format!("Only objects can be spread into an object, got '{}'.", value),
+              )),
+            },
+          }
+        }
+
+        Ok(RuntimeValue::Object(Rc::new(RefCell::new(object))))
+      },
+
+      Expr::StructLiteral { name, variant, properties, span } => {
+        let value = self.environment.borrow().get(name)?;
+
+        match (value, variant) {
+          (RuntimeValue::Struct(def), None) => {
+            let base = |value: &RuntimeValue| match value {
+              RuntimeValue::Instance(other) if Rc::ptr_eq(&other.def, &def) => Some(other.fields.borrow().clone()),
+              _ => None,
+            };
+            let fields = self.literal_fields(&def.name, &def.fields, properties, *span, base)?;
+
+            Ok(RuntimeValue::Instance(Rc::new(Instance::new(Rc::clone(&def), fields))))
+          },
+          (RuntimeValue::Enum(def), Some(variant)) => {
+            let index = self.variant(&def, variant)?;
+            let VariantKind::Named(names) = &def.variants[index].kind else {
+              return Err(ShiftError::runtime(
+                codes::TYPE_MISMATCH,
+                variant.span,
+                format!("'{}.{}' has no named fields.", def.name, variant.lexeme),
+              ));
+            };
+
+            let base = |value: &RuntimeValue| match value {
+              RuntimeValue::Variant(other) if Rc::ptr_eq(&other.def, &def) && other.variant == index => match &other.payload {
+                Payload::Named(fields) => Some(fields.clone()),
+                _ => None,
+              },
+              _ => None,
+            };
+            let type_name = format!("{}.{}", def.name, variant.lexeme);
+            let fields = self.literal_fields(&type_name, names, properties, *span, base)?;
+
+            let value = EnumValue { def: Rc::clone(&def), variant: index, payload: Payload::Named(fields) };
+            Ok(RuntimeValue::Variant(Rc::new(value)))
+          },
+          (value, None) => Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            name.span,
+            format!("'{}' is not a struct.", value),
+          )),
+          (value, Some(_)) => Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            name.span,
+            format!("'{}' is not an enum.", value),
+          )),
+        }
+      },
+
+      Expr::Match { subject, arms, .. } => {
+        let (arm, environment) = self.select_arm(subject, arms)?;
+        let Stmt::Expression { expression } = &arm.body else {
+          unreachable!("o parser só aceita blocos no match usado como instrução")
+        };
+
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = self.evaluate(expression);
+        self.environment = previous;
+        result
+      },
+
+      Expr::Set { object, name, value, .. } => {
+        let place = self.property(object, name)?;
+        let value = self.evaluate(value)?;
+        self.write(place, value.clone())?;
+        Ok(value)
+      },
+
+      Expr::Index { .. } => {
+        Ok(self.evaluate_chain(expr)?.unwrap_or(RuntimeValue::Null))
+      },
+
+      Expr::SetIndex { object, index, value, .. } => {
+        let place = self.element(object, index)?;
+        let value = self.evaluate(value)?;
+        self.write(place, value.clone())?;
+        Ok(value)
+      },
+
+      Expr::CompoundAssign { target, operator, value, .. } => {
+        let place = self.place(target)?;
+        let current = self.read(&place)?;
+        let value = self.evaluate(value)?;
+        let operator = operator.compound_operator().expect("o parser só cria CompoundAssign com +=, -=, *= e /=");
+
+        let result = self.evaluate_binary(current, &operator, value)?;
+        self.write(place, result.clone())?;
+        Ok(result)
+      },
+
+      Expr::Update { target, operator, prefix, .. } => {
+        let place = self.place(target)?;
+        let old = self.read(&place)?;
+        let new = match (&old, &operator.token_type) {
+          (RuntimeValue::Integer(i), TokenType::Increment) => i.checked_add(1).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator))?,
+          (RuntimeValue::Integer(i), _) => i.checked_sub(1).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator))?,
+          (RuntimeValue::Float(f), TokenType::Increment) => RuntimeValue::Float(f + 1.0),
+          (RuntimeValue::Float(f), _) => RuntimeValue::Float(f - 1.0),
+          _ => return Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            operator.span,
+            format!("The operator '{}' requires a number.", operator.lexeme),
+          )),
+        };
+
+        self.write(place, new.clone())?;
+        Ok(if *prefix { new } else { old })
+      },
+
+    }
+  }
+
+  // Campos de `User { .. }` ou `Shape.Rect { .. }`, na ordem da declaração.
+  // `base` diz de quais valores um `...valor` pode copiar os campos.
+  fn literal_fields(
+    &mut self,
+    type_name: &str,
+    names: &[String],
+    properties: &[Property],
+    span: Span,
+    base: impl Fn(&RuntimeValue) -> Option<Object>,
+  ) -> Result<Object, ShiftError> {
+    let mut given = Object::new();
+    for property in properties {
+      match property {
+        Property::Field { key, key_span, value } => {
+          if !names.contains(key) {
+            return Err(ShiftError::runtime(
+              codes::UNDEFINED_PROPERTY,
+              *key_span,
+              format!("'{}' has no field '{}'.", type_name, key),
+            ));
+          }
+          let value = self.evaluate(value)?;
+          given.set(key, value);
+        },
+        // User { ...outro, age: 31 }
+        Property::Spread(expression) => {
+          let value = self.evaluate(expression)?;
+          let Some(fields) = base(&value) else {
+            return Err(ShiftError::runtime(
+              codes::TYPE_MISMATCH,
+              expression.span(),
+              format!("Only a '{}' can be spread into a '{}', got '{}'.", type_name, type_name, value),
+            ));
+          };
+
+          for (key, value) in fields.iter() {
+            given.set(key, value.clone());
+          }
+        },
+      }
+    }
+
+    let mut fields = Object::new();
+    for name in names {
+      match given.get(name) {
+        Some(value) => fields.set(name, value.clone()),
+        None => return Err(ShiftError::runtime(
+          codes::UNDEFINED_PROPERTY,
+          span,
+          format!("Missing field '{}' in '{}'.", name, type_name),
+        )),
+      }
+    }
+
+    Ok(fields)
+  }
+
+  fn variant(&self, def: &EnumDef, name: &Token) -> Result<usize, ShiftError> {
+    def.variant(&name.lexeme).ok_or_else(|| ShiftError::runtime(
+      codes::UNDEFINED_PROPERTY,
+      name.span,
+      format!("Enum '{}' has no variant '{}'.", def.name, name.lexeme),
+    ))
+  }
+
+  // Confere o `impl Trait for Nome` contra o trait e registra no struct
+  // os métodos do bloco e os padrão que ele não redefiniu
+  fn implement_trait(&self, def: &StructDef, name: &Token, trait_name: &Token, methods: &[Method]) -> Result<(), ShiftError> {
+    let trait_def = match self.environment.borrow().get(trait_name)? {
+      RuntimeValue::Trait(trait_def) => trait_def,
+      value => return Err(ShiftError::runtime(
+        codes::TYPE_MISMATCH,
+        trait_name.span,
+        format!("Can only implement traits, got '{}'.", value),
+      )),
+    };
+
+    for method in methods {
+      let method_token = method.declaration.name.as_ref().unwrap_or(name);
+      let Some(expected) = trait_def.method(method_name(method)) else {
+        return Err(ShiftError::runtime(
+          codes::TRAIT_MISMATCH,
+          method_token.span,
+          format!("Method '{}' is not a member of trait '{}'.", method_token.lexeme, trait_def.name),
+        ));
+      };
+
+      if expected.has_self != method.has_self {
+        let takes = if expected.has_self { "takes" } else { "does not take" };
+        return Err(ShiftError::runtime(
+          codes::TRAIT_MISMATCH,
+          method_token.span,
+          format!("Method '{}' of trait '{}' {} 'self'.", expected.name, trait_def.name, takes),
+        ));
+      }
+
+      if expected.arity != method.declaration.params.len() {
+        return Err(ShiftError::runtime(
+          codes::TRAIT_MISMATCH,
+          method_token.span,
+          format!(
+            "Method '{}' of trait '{}' expects {} parameters but got {}.",
+            expected.name, trait_def.name, expected.arity, method.declaration.params.len(),
+          ),
+        ));
+      }
+    }
+
+    for expected in &trait_def.methods {
+      if methods.iter().any(|method| method_name(method) == expected.name) {
+        continue;
+      }
+
+      let Some(default) = &expected.default else {
+        return Err(ShiftError::runtime(
+          codes::TRAIT_MISMATCH,
+          name.span,
+          format!("Missing method '{}' of trait '{}' in impl for '{}'.", expected.name, trait_def.name, def.name),
+        ).with_label(trait_name.span, "required by this trait"));
+      };
+      def.add_trait_method(&trait_def.name, &expected.name, StructMethod { function: Rc::clone(default), has_self: expected.has_self });
+    }
+
+    for method in methods {
+      let function = Function::new(Rc::clone(&method.declaration), Rc::clone(&self.environment));
+      let method_def = StructMethod { function: Rc::new(function), has_self: method.has_self };
+      def.add_trait_method(&trait_def.name, method_name(method), method_def);
+    }
+
+    Ok(())
+  }
+
+  // Primeiro braço cujo padrão e guarda aceitam o valor, junto com o escopo
+  // onde ficam as variáveis criadas pelo padrão
+  fn select_arm<'a>(&mut self, subject: &Expr, arms: &'a [MatchArm]) -> Result<(&'a MatchArm, Rc<RefCell<Environment>>), ShiftError> {
+    let value = self.evaluate(subject)?;
+
+    for arm in arms {
+      let mut bindings = Vec::new();
+      if !self.matches(&arm.pattern, &value, &mut bindings)? {
+        continue;
+      }
+
+      let mut environment = Environment::new_enclosed(Rc::clone(&self.environment));
+      for (name, value) in bindings {
+        environment.define(&name, value, false);
       }
+      let environment = Rc::new(RefCell::new(environment));
+
+      if let Some(guard) = &arm.guard {
+        let previous = std::mem::replace(&mut self.environment, Rc::clone(&environment));
+        let accepted = self.evaluate(guard);
+        self.environment = previous;
+
+        if !self.is_truthy(&accepted?) {
+          continue;
+        }
+      }
+
+      return Ok((arm, environment));
+    }
+
+    Err(ShiftError::runtime(
+      codes::NO_MATCH,
+      subject.span(),
+      format!("No match arm matched the value '{}'.", value),
+    ).with_help("add a '_ => ...' arm for the remaining values"))
+  }
+
+  // Confere o padrão e guarda em `bindings` as variáveis que ele cria
+  fn matches(&self, pattern: &Pattern, value: &RuntimeValue, bindings: &mut Vec<(String, RuntimeValue)>) -> Result<bool, ShiftError> {
+    match pattern {
+      Pattern::Wildcard(_) => Ok(true),
+      Pattern::Binding(name) => {
+        bindings.push((name.lexeme.clone(), value.clone()));
+        Ok(true)
+      },
+      Pattern::Literal { value: expected, .. } => Ok(*value == literal(expected)),
+      Pattern::Range { start, end, .. } => Ok(in_range(value, start, end)),
+      Pattern::Variant { enum_name, variant, fields, span } => {
+        let def = match self.environment.borrow().get(enum_name)? {
+          RuntimeValue::Enum(def) => def,
+          other => return Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            enum_name.span,
+            format!("'{}' is not an enum.", other),
+          )),
+        };
+        let index = self.variant(&def, variant)?;
+
+        // O formato do padrão é conferido mesmo quando a variante é outra
+        let shape_matches = match (fields, &def.variants[index].kind) {
+          (FieldPatterns::Unit, VariantKind::Unit) => true,
+          (FieldPatterns::Tuple(patterns), VariantKind::Tuple(count)) => patterns.len() == *count,
+          (FieldPatterns::Named(fields), VariantKind::Named(names)) => fields.iter().all(|(name, _)| names.contains(&name.lexeme)),
+          _ => false,
+        };
+        if !shape_matches {
+          return Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            *span,
+            format!("Pattern '{}' does not fit the fields of '{}.{}'.", pattern, def.name, variant.lexeme),
+          ));
+        }
+
+        let RuntimeValue::Variant(actual) = value else {
+          return Ok(false);
+        };
+        if !Rc::ptr_eq(&actual.def, &def) || actual.variant != index {
+          return Ok(false);
+        }
+
+        match (fields, &actual.payload) {
+          (FieldPatterns::Tuple(patterns), Payload::Tuple(values)) => {
+            for (pattern, value) in patterns.iter().zip(values) {
+              if !self.matches(pattern, value, bindings)? {
+                return Ok(false);
+              }
+            }
+            Ok(true)
+          },
+          (FieldPatterns::Named(fields), Payload::Named(object)) => {
+            for (name, pattern) in fields {
+              let value = object.get(&name.lexeme).cloned().unwrap_or(RuntimeValue::Null);
+              if !self.matches(pattern, &value, bindings)? {
+                return Ok(false);
+              }
+            }
+            Ok(true)
+          },
+          _ => Ok(true),
+        }
+      },
+    }
+  }
+
+  // Leituras, índices e chamadas formam uma cadeia. `None` quando um `?.`
+  // encontrou null: aí o resto da cadeia é pulado e `o?.a.b` vale null
+  fn evaluate_chain(&mut self, expr: &Expr) -> Result<Option<RuntimeValue>, ShiftError> {
+    match expr {
+      Expr::Get { object, name, optional, .. } => match self.evaluate_chain(object)? {
+        Some(object) => self.evaluate_get(object, name, *optional),
+        None => Ok(None),
+      },
+      Expr::Index { object, index, .. } => match self.evaluate_chain(object)? {
+        Some(value) => {
+          let place = self.element_of(value, object, index)?;
+          self.read(&place).map(Some)
+        },
+        None => Ok(None),
+      },
+      Expr::Call { callee, paren, arguments, .. } => {
+        let callee = match callee.as_ref() {
+          Expr::Get { object, name, optional, .. } => match self.evaluate_chain(object)? {
+            Some(object) => self.evaluate_get(object, name, *optional)?,
+            None => None,
+          },
+          callee => self.evaluate_chain(callee)?,
+        };
+        // obj?.metodo() não chama nada quando obj é null
+        let Some(callee) = callee else {
+          return Ok(None);
+        };
+
+        let mut values = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+          values.push(self.evaluate(argument)?);
+        }
+
+        let function: Rc<dyn Callable> = match callee {
+          RuntimeValue::Function(function) => function,
+          RuntimeValue::NativeFunction(function) => function,
+          _ => return Err(ShiftError::runtime(codes::NOT_CALLABLE, paren.span, "Can only call functions.")),
+        };
+
+        if values.len() != function.arity() {
+          return Err(ShiftError::runtime(
+            codes::ARITY_MISMATCH,
+            paren.span,
+            format!("Expected {} arguments but got {}.", function.arity(), values.len()),
+          ));
+        }
+
+        // Funções nativas não sabem de onde foram chamadas
+        function.call(self, values).map(Some).map_err(|mut error| {
+          error.span.get_or_insert(expr.span());
+          error
+        })
+      },
+      _ => self.evaluate(expr).map(Some),
+    }
+  }
+
+  // `None` quando o `?.` encontrou null
+  fn evaluate_get(&mut self, object: RuntimeValue, name: &Token, optional: bool) -> Result<Option<RuntimeValue>, ShiftError> {
+    match object {
+      // Propriedade que não existe vale null, como em `obj.talvez ?? padrao`
+      RuntimeValue::Object(object) => Ok(Some(object.borrow().get(&name.lexeme).cloned().unwrap_or(RuntimeValue::Null))),
+      RuntimeValue::Instance(instance) => {
+        if let Some(value) = instance.fields.borrow().get(&name.lexeme) {
+          return Ok(Some(value.clone()));
+        }
+
+        match struct_method(&instance.def, name)? {
+          Some(method) if method.has_self => {
+            let bound = method.function.bind(RuntimeValue::Instance(Rc::clone(&instance)));
+            Ok(Some(RuntimeValue::Function(Rc::new(bound))))
+          },
+          Some(_) => Err(ShiftError::runtime(
+            codes::UNDEFINED_PROPERTY,
+            name.span,
+            format!("'{}' has no 'self', call it as '{}.{}()'.", name.lexeme, instance.def.name, name.lexeme),
+          )),
+          None => Err(ShiftError::runtime(
+            codes::UNDEFINED_PROPERTY,
+            name.span,
+            format!("'{}' has no field or method '{}'.", instance.def.name, name.lexeme),
+          )),
+        }
+      },
+      // Shape.Empty, ou o construtor de Shape.Circle(..)
+      RuntimeValue::Enum(def) => {
+        let index = self.variant(&def, name)?;
+
+        match &def.variants[index].kind {
+          VariantKind::Unit => {
+            let value = EnumValue { def: Rc::clone(&def), variant: index, payload: Payload::Unit };
+            Ok(Some(RuntimeValue::Variant(Rc::new(value))))
+          },
+          VariantKind::Tuple(count) => {
+            let constructor_def = Rc::clone(&def);
+            let constructor = NativeFunction::new(
+              &format!("{}.{}", def.name, name.lexeme),
+              *count,
+              Box::new(move |arguments| {
+                let payload = Payload::Tuple(arguments.to_vec());
+                let value = EnumValue { def: Rc::clone(&constructor_def), variant: index, payload };
+                Ok(RuntimeValue::Variant(Rc::new(value)))
+              }),
+            );
+            Ok(Some(RuntimeValue::NativeFunction(Rc::new(constructor))))
+          },
+          VariantKind::Named(_) => Err(ShiftError::runtime(
+            codes::TYPE_MISMATCH,
+            name.span,
+            format!("'{}.{}' has named fields, create it with '{}.{} {{ ... }}'.", def.name, name.lexeme, def.name, name.lexeme),
+          )),
+        }
+      },
+      // User.new(), só para métodos sem `self`
+      RuntimeValue::Struct(def) => match struct_method(&def, name)? {
+        Some(method) if !method.has_self => Ok(Some(RuntimeValue::Function(method.function))),
+        Some(_) => Err(ShiftError::runtime(
+          codes::UNDEFINED_PROPERTY,
+          name.span,
+          format!("Method '{}' takes 'self' and must be called on a '{}' value.", name.lexeme, def.name),
+        )),
+        None => Err(ShiftError::runtime(
+          codes::UNDEFINED_PROPERTY,
+          name.span,
+          format!("Struct '{}' has no method '{}'.", def.name, name.lexeme),
+        )),
+      },
+      RuntimeValue::Null if optional => Ok(None),
+      RuntimeValue::Null => Err(ShiftError::runtime(
+        codes::TYPE_MISMATCH,
+        name.span,
+        format!("Cannot read property '{}' of null.", name.lexeme),
+      ).with_help("use '?.' to get null when the value is missing")),
+      value => Err(ShiftError::runtime(
+        codes::TYPE_MISMATCH,
+        name.span,
+        format!("Cannot read property '{}' of {}.", name.lexeme, value),
+      )),
+    }
+  }
+
+  // Avalia as partes de um alvo que o parser já validou como atribuível
+  fn place(&mut self, target: &Expr) -> Result<Place, ShiftError> {
+    match target {
+      Expr::Variable { name, .. } => Ok(Place::Variable(name.clone())),
+      Expr::Index { object, index, .. } => self.element(object, index),
+      Expr::Get { object, name, .. } => self.property(object, name),
+      _ => unreachable!("o parser só aceita alvos atribuíveis"),
+    }
+  }
+
+  fn property(&mut self, object: &Expr, name: &Token) -> Result<Place, ShiftError> {
+    match self.evaluate(object)? {
+      RuntimeValue::Object(object) => Ok(Place::Property(object, name.lexeme.clone())),
+      // Structs não ganham campos novos
+      RuntimeValue::Instance(instance) if instance.def.has_field(&name.lexeme) => {
+        Ok(Place::Property(Rc::clone(&instance.fields), name.lexeme.clone()))
+      },
+      RuntimeValue::Instance(instance) => Err(ShiftError::runtime(
+        codes::UNDEFINED_PROPERTY,
+        name.span,
+        format!("'{}' has no field '{}'.", instance.def.name, name.lexeme),
+      )),
+      value => Err(ShiftError::runtime(
+        codes::TYPE_MISMATCH,
+        name.span,
+        format!("Cannot set property '{}' on {}.", name.lexeme, value),
+      )),
+    }
+  }
+
+  fn element(&mut self, object: &Expr, index: &Expr) -> Result<Place, ShiftError> {
+    let value = self.evaluate(object)?;
+    self.element_of(value, object, index)
+  }
+
+  fn element_of(&mut self, value: RuntimeValue, object: &Expr, index: &Expr) -> Result<Place, ShiftError> {
+    let items = match value {
+      RuntimeValue::Array(items) => items,
+      // obj["chave"]
+      RuntimeValue::Object(object) => return match self.evaluate(index)? {
+        RuntimeValue::String(key) => Ok(Place::Property(object, key)),
+        value => Err(ShiftError::runtime(
+          codes::TYPE_MISMATCH,
+          index.span(),
+          format!("Object keys must be strings, got '{}'.", value),
+        )),
+      },
+      value => return Err(ShiftError::runtime(
+        codes::TYPE_MISMATCH,
+        object.span(),
+        format!("Only arrays and objects can be indexed, got '{}'.", value),
+      )),
+    };
+
+    let position = match self.evaluate(index)? {
+      RuntimeValue::Integer(i) => i,
+      value => return Err(ShiftError::runtime(
+        codes::TYPE_MISMATCH,
+        index.span(),
+        format!("Array index must be an integer, got '{}'.", value),
+      )),
+    };
+
+    // -1 é o último elemento
+    let len = items.borrow().len();
+    let resolved = if position < 0 { position.checked_add(len as i64) } else { Some(position) };
+
+    match resolved.and_then(|i| usize::try_from(i).ok()).filter(|i| *i < len) {
+      Some(i) => Ok(Place::Element(items, i, index.span())),
+      None => Err(out_of_bounds(index.span(), position, len)),
+    }
+  }
+
+  // Avaliar o valor (ou uma função nativa) pode ter encolhido o array
+  // depois do índice ser conferido, então confere de novo
+  fn read(&self, place: &Place) -> Result<RuntimeValue, ShiftError> {
+    match place {
+      Place::Variable(name) => self.environment.borrow().get(name),
+      Place::Element(items, i, span) => {
+        let items = items.borrow();
+        items.get(*i).cloned().ok_or_else(|| out_of_bounds(*span, *i as i64, items.len()))
+      },
+      Place::Property(object, key) => Ok(object.borrow().get(key).cloned().unwrap_or(RuntimeValue::Null)),
+    }
+  }
 
+  fn write(&mut self, place: Place, value: RuntimeValue) -> Result<(), ShiftError> {
+    match place {
+      Place::Variable(name) => self.environment.borrow_mut().assign(&name, value),
+      Place::Element(items, i, span) => {
+        let mut items = items.borrow_mut();
+        let len = items.len();
+        let slot = items.get_mut(i).ok_or_else(|| out_of_bounds(span, i as i64, len))?;
+        *slot = value;
+        Ok(())
+      },
+      Place::Property(object, key) => {
+        object.borrow_mut().set(&key, value);
+        Ok(())
+      },
     }
   }
 
-  fn evaluate_unary(&self, operator: TokenType, right: RuntimeValue) -> Result<RuntimeValue, String> {
-    match operator {
+  fn evaluate_unary(&self, operator: &Token, right: RuntimeValue) -> Result<RuntimeValue, ShiftError> {
+    match operator.token_type {
           TokenType::Minus => {
             match right {
-              RuntimeValue::Integer(i) => Ok(RuntimeValue::Integer(-i)),
+              RuntimeValue::Integer(i) => i.checked_neg().map(RuntimeValue::Integer).ok_or_else(|| overflow(operator)),
               RuntimeValue::Float(f) => Ok(RuntimeValue::Float(-f)),
-              _ => Err("Need be a number".to_string())
+              _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "Need be a number"))
             }
           },
           TokenType::Bang => {
             Ok(RuntimeValue::Boolean(!self.is_truthy(&right)))
           },
-          _ => Err("Invalid operator".to_string())
+          TokenType::BitNot => {
+            match right {
+              RuntimeValue::Integer(i) => Ok(RuntimeValue::Integer(!i)),
+              _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "The operator '~' requires an integer."))
+            }
+          },
+          _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "Invalid operator"))
         }
   }
 
-  fn evaluate_binary(&self, left: RuntimeValue, operator: TokenType, right: RuntimeValue) -> Result<RuntimeValue, String> {
-    match operator {
-        // --- Aritmética (Menos, Vezes, Divisão) ---
-        TokenType::Minus | TokenType::Slash | TokenType::Star => {
+  fn evaluate_binary(&self, left: RuntimeValue, operator: &Token, right: RuntimeValue) -> Result<RuntimeValue, ShiftError> {
+    match operator.token_type {
+        // --- Aritmética (Menos, Vezes, Divisão, Resto) ---
+        TokenType::Minus | TokenType::Slash | TokenType::Star | TokenType::Percent => {
             match (left, right) {
-                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => match operator {
-                    TokenType::Minus => Ok(RuntimeValue::Integer(l - r)),
-                    TokenType::Star => Ok(RuntimeValue::Integer(l * r)),
+                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => match operator.token_type {
+                    TokenType::Minus => l.checked_sub(r).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator)),
+                    TokenType::Star => l.checked_mul(r).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator)),
                     TokenType::Slash => {
-                        if r == 0 { return Err("Divisão por zero".to_string()); }
-                        Ok(RuntimeValue::Integer(l / r)) // Divisão Inteira
+                        if r == 0 { return Err(ShiftError::runtime(codes::DIVISION_BY_ZERO, operator.span, "Division by zero.")); }
+                        // Divisão Inteira, só estoura em i64::MIN / -1
+                        l.checked_div(r).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator))
+                    },
+                    TokenType::Percent => {
+                        if r == 0 { return Err(ShiftError::runtime(codes::DIVISION_BY_ZERO, operator.span, "Modulo by zero.")); }
+                        l.checked_rem(r).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator))
                     },
                     _ => unreachable!(),
                 },
-                (RuntimeValue::Float(l), RuntimeValue::Float(r)) => match operator {
+                (RuntimeValue::Float(l), RuntimeValue::Float(r)) => match operator.token_type {
                     TokenType::Minus => Ok(RuntimeValue::Float(l - r)),
                     TokenType::Star => Ok(RuntimeValue::Float(l * r)),
                     TokenType::Slash => Ok(RuntimeValue::Float(l / r)),
+                    TokenType::Percent => Ok(RuntimeValue::Float(l % r)),
                     _ => unreachable!(),
                 },
                 // Mistos (Int e Float) -> Promove para Float
                 (RuntimeValue::Integer(l), RuntimeValue::Float(r)) => {
                     let l = l as f64;
-                    match operator {
+                    match operator.token_type {
                         TokenType::Minus => Ok(RuntimeValue::Float(l - r)),
                         TokenType::Star => Ok(RuntimeValue::Float(l * r)),
                         TokenType::Slash => Ok(RuntimeValue::Float(l / r)),
+                        TokenType::Percent => Ok(RuntimeValue::Float(l % r)),
                         _ => unreachable!(),
                     }
                 },
                 (RuntimeValue::Float(l), RuntimeValue::Integer(r)) => {
                     let r = r as f64;
-                    match operator {
+                    match operator.token_type {
                         TokenType::Minus => Ok(RuntimeValue::Float(l - r)),
                         TokenType::Star => Ok(RuntimeValue::Float(l * r)),
                         TokenType::Slash => Ok(RuntimeValue::Float(l / r)),
+                        TokenType::Percent => Ok(RuntimeValue::Float(l % r)),
                         _ => unreachable!(),
                     }
                 },
-                _ => Err("Math operations require numbers".to_string())
+                _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "Math operations require numbers"))
             }
         },
 
         // --- Soma e Concatenação ---
         TokenType::Plus => {
             match (left, right) {
-                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => Ok(RuntimeValue::Integer(l + r)),
+                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => l.checked_add(r).map(RuntimeValue::Integer).ok_or_else(|| overflow(operator)),
                 (RuntimeValue::Float(l), RuntimeValue::Float(r))     => Ok(RuntimeValue::Float(l + r)),
                 (RuntimeValue::Integer(l), RuntimeValue::Float(r))   => Ok(RuntimeValue::Float(l as f64 + r)),
                 (RuntimeValue::Float(l), RuntimeValue::Integer(r))   => Ok(RuntimeValue::Float(l + r as f64)),
                 (RuntimeValue::String(l), RuntimeValue::String(r))   => Ok(RuntimeValue::String(format!("{}{}", l, r))),
-                _ => Err("The operator '+' requires to numbers or to strings".to_string())
+                _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "The operator '+' requires two numbers or two strings."))
+            }
+        },
+
+        // --- Potência: sempre em float, então `2 ** -1` vale 0.5 ---
+        TokenType::StarStar => {
+            let number = |value: &RuntimeValue| match value {
+                RuntimeValue::Integer(i) => Some(*i as f64),
+                RuntimeValue::Float(f) => Some(*f),
+                _ => None,
+            };
+            match (number(&left), number(&right)) {
+                (Some(l), Some(r)) => Ok(RuntimeValue::Float(l.powf(r))),
+                _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "The operator '**' requires two numbers."))
+            }
+        },
+
+        // --- Bits: só inteiros ---
+        TokenType::BitAnd | TokenType::OrBitwise | TokenType::BitXor | TokenType::ShiftLeft | TokenType::ShiftRight => {
+            let (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) = (left, right) else {
+                return Err(ShiftError::runtime(
+                    codes::TYPE_MISMATCH,
+                    operator.span,
+                    format!("The operator '{}' requires two integers.", operator.lexeme),
+                ));
+            };
+
+            match operator.token_type {
+                TokenType::BitAnd => Ok(RuntimeValue::Integer(l & r)),
+                TokenType::OrBitwise => Ok(RuntimeValue::Integer(l | r)),
+                TokenType::BitXor => Ok(RuntimeValue::Integer(l ^ r)),
+                _ => {
+                    let shifted = u32::try_from(r).ok().and_then(|r| match operator.token_type {
+                        TokenType::ShiftLeft => l.checked_shl(r),
+                        _ => l.checked_shr(r),
+                    });
+                    shifted.map(RuntimeValue::Integer).ok_or_else(|| ShiftError::runtime(
+                        codes::INTEGER_OVERFLOW,
+                        operator.span,
+                        format!("Shift amount {} is out of range, it must be between 0 and 63.", r),
+                    ))
+                },
+            }
+        },
+
+        // --- Aritmética que dá a volta (+%) ou satura no limite (+|) ---
+        TokenType::WrappingPlus | TokenType::WrappingMinus | TokenType::WrappingStar
+        | TokenType::SaturatingPlus | TokenType::SaturatingMinus | TokenType::SaturatingStar => {
+            match (left, right) {
+                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => Ok(RuntimeValue::Integer(match operator.token_type {
+                    TokenType::WrappingPlus    => l.wrapping_add(r),
+                    TokenType::WrappingMinus   => l.wrapping_sub(r),
+                    TokenType::WrappingStar    => l.wrapping_mul(r),
+                    TokenType::SaturatingPlus  => l.saturating_add(r),
+                    TokenType::SaturatingMinus => l.saturating_sub(r),
+                    TokenType::SaturatingStar  => l.saturating_mul(r),
+                    _ => unreachable!(),
+                })),
+                _ => Err(ShiftError::runtime(
+                    codes::TYPE_MISMATCH,
+                    operator.span,
+                    format!("The operator '{}' requires two integers.", operator.lexeme),
+                ))
             }
         },
 
         // --- Comparação (>, <, >=, <=) ---
         TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
             match (left, right) {
-                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => match operator {
+                (RuntimeValue::Integer(l), RuntimeValue::Integer(r)) => match operator.token_type {
                     TokenType::Greater      => Ok(RuntimeValue::Boolean(l > r)),
                     TokenType::GreaterEqual => Ok(RuntimeValue::Boolean(l >= r)),
                     TokenType::Less         => Ok(RuntimeValue::Boolean(l < r)),
                     TokenType::LessEqual    => Ok(RuntimeValue::Boolean(l <= r)),
                     _ => unreachable!(),
                 },
-                (RuntimeValue::Float(l), RuntimeValue::Float(r)) => match operator {
+                (RuntimeValue::Float(l), RuntimeValue::Float(r)) => match operator.token_type {
                     TokenType::Greater      => Ok(RuntimeValue::Boolean(l > r)),
                     TokenType::GreaterEqual => Ok(RuntimeValue::Boolean(l >= r)),
                     TokenType::Less         => Ok(RuntimeValue::Boolean(l < r)),
@@ -167,7 +1326,7 @@ impl Interpreter {
                 // Mistos
                 (RuntimeValue::Integer(l), RuntimeValue::Float(r)) => {
                     let l = l as f64;
-                    match operator {
+                    match operator.token_type {
                         TokenType::Greater      => Ok(RuntimeValue::Boolean(l > r)),
                         TokenType::GreaterEqual => Ok(RuntimeValue::Boolean(l >= r)),
                         TokenType::Less         => Ok(RuntimeValue::Boolean(l < r)),
@@ -177,7 +1336,7 @@ impl Interpreter {
                 },
                 (RuntimeValue::Float(l), RuntimeValue::Integer(r)) => {
                     let r = r as f64;
-                    match operator {
+                    match operator.token_type {
                         TokenType::Greater      => Ok(RuntimeValue::Boolean(l > r)),
                         TokenType::GreaterEqual => Ok(RuntimeValue::Boolean(l >= r)),
                         TokenType::Less         => Ok(RuntimeValue::Boolean(l < r)),
@@ -185,7 +1344,7 @@ impl Interpreter {
                         _ => unreachable!(),
                     }
                 },
-                _ => Err("Operadores de comparação requerem números".to_string())
+                _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "Comparison operators require numbers."))
             }
         },
 
@@ -193,7 +1352,7 @@ impl Interpreter {
         TokenType::EqualEqual => Ok(RuntimeValue::Boolean(left == right)),
         TokenType::BangEqual  => Ok(RuntimeValue::Boolean(left != right)),
 
-        _ => Err("Operador desconhecido ou não suportado em binários".to_string())
+        _ => Err(ShiftError::runtime(codes::TYPE_MISMATCH, operator.span, "Operador desconhecido ou não suportado em binários"))
     }
   }
 
@@ -205,4 +1364,73 @@ impl Interpreter {
     }
   }
 
-}
\ No newline at end of file
+}
+
+fn overflow(operator: &Token) -> ShiftError {
+  let error = ShiftError::runtime(codes::INTEGER_OVERFLOW, operator.span, format!("Integer overflow in '{}'.", operator.lexeme));
+
+  match operator.token_type {
+    TokenType::Plus | TokenType::Minus | TokenType::Star => error.with_help(format!(
+      "use '{0}%' to wrap around or '{0}|' to stop at the limit",
+      operator.lexeme
+    )),
+    _ => error,
+  }
+}
+
+fn out_of_bounds(span: Span, index: i64, len: usize) -> ShiftError {
+  ShiftError::runtime(
+    codes::INDEX_OUT_OF_BOUNDS,
+    span,
+    format!("Index {} is out of bounds for an array of length {}.", index, len),
+  )
+}
+
+fn literal(value: &LiteralValue) -> RuntimeValue {
+  match value {
+    LiteralValue::Integer(i) => RuntimeValue::Integer(*i),
+    LiteralValue::Float(f) => RuntimeValue::Float(*f),
+    LiteralValue::String(s) => RuntimeValue::String(s.clone()),
+    LiteralValue::Boolean(b) => RuntimeValue::Boolean(*b),
+    LiteralValue::Null => RuntimeValue::Null,
+  }
+}
+
+// Declarações que o `hoist` executa antes do resto do programa
+fn is_item(statement: &Stmt) -> bool {
+  matches!(
+    statement,
+    Stmt::Function { .. } | Stmt::Struct { .. } | Stmt::Enum { .. } | Stmt::Trait { .. } | Stmt::Impl { .. }
+  )
+}
+
+fn struct_method(def: &StructDef, name: &Token) -> Result<Option<StructMethod>, ShiftError> {
+  def.method(&name.lexeme).map_err(|traits| {
+    let traits: Vec<String> = traits.iter().map(|trait_name| format!("'{}'", trait_name)).collect();
+    ShiftError::runtime(
+      codes::TRAIT_MISMATCH,
+      name.span,
+      format!("Method '{}' of '{}' is ambiguous, it comes from traits {}.", name.lexeme, def.name, traits.join(" and ")),
+    ).with_help(format!("rename the method in one of the traits or add 'fn {}' to 'impl {}'", name.lexeme, def.name))
+  })
+}
+
+fn method_name(method: &Method) -> &str {
+  method.declaration.name.as_ref().map_or("", |name| name.lexeme.as_str())
+}
+
+// `start..end` sem o fim; int e float se misturam como nas comparações
+fn in_range(value: &RuntimeValue, start: &LiteralValue, end: &LiteralValue) -> bool {
+  let number = |literal: &LiteralValue| match literal {
+    LiteralValue::Integer(i) => *i as f64,
+    LiteralValue::Float(f) => *f,
+    _ => f64::NAN,
+  };
+
+  match (value, start, end) {
+    (RuntimeValue::Integer(v), LiteralValue::Integer(start), LiteralValue::Integer(end)) => start <= v && v < end,
+    (RuntimeValue::Integer(v), start, end) => number(start) <= *v as f64 && (*v as f64) < number(end),
+    (RuntimeValue::Float(v), start, end) => number(start) <= *v && *v < number(end),
+    _ => false,
+  }
+}
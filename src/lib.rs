@@ -0,0 +1,65 @@
+//! Shift: scanner, parser, interpretador e transpilador para Rust.
+//!
+//! O jeito mais simples de usar é pelo [`run_source`]:
+//!
+//! ```
+//! let value = shift::run_source("(10 * 10) / 2").unwrap();
+//! assert_eq!(value, shift::RuntimeValue::Integer(50));
+//! ```
+
+pub mod scanner;
+pub mod token;
+pub mod errors;
+pub mod expr;
+pub mod parser;
+pub mod interpreter;
+pub mod function;
+pub mod object;
+pub mod structs;
+pub mod enums;
+pub mod traits;
+pub mod codegen;
+pub mod diagnostics;
+pub mod lint;
+mod environment;
+mod natives;
+
+pub use crate::errors::{ErrorKind, ShiftError};
+pub use crate::expr::{Expr, Stmt};
+pub use crate::interpreter::{Interpreter, RuntimeValue};
+pub use crate::parser::Parser;
+pub use crate::scanner::Scanner;
+
+/// Roda um script do zero e devolve o valor da última expressão.
+/// Se o código tiver vários erros de sintaxe, só o primeiro é devolvido;
+/// use [`parse`] para ter todos.
+pub fn run_source(source: &str) -> Result<RuntimeValue, ShiftError> {
+  let mut interpreter = Interpreter::new();
+  run(&mut interpreter, source)
+}
+
+/// Roda um script num interpretador existente, mantendo as variáveis
+/// entre chamadas (é o que o REPL usa)
+pub fn run(interpreter: &mut Interpreter, source: &str) -> Result<RuntimeValue, ShiftError> {
+  let statements = parse(source).map_err(first)?;
+
+  interpreter.interpret(&statements)
+}
+
+/// Transpila um script para o código de um `main.rs`
+pub fn transpile(source: &str) -> Result<String, ShiftError> {
+  let statements = parse(source).map_err(first)?;
+
+  codegen::generate(&statements)
+}
+
+/// Scanner + parser, devolvendo todos os erros encontrados
+pub fn parse(source: &str) -> Result<Vec<Stmt>, Vec<ShiftError>> {
+  let tokens = Scanner::new(source).scan_tokens()?;
+
+  Parser::new(tokens).parse()
+}
+
+fn first(mut errors: Vec<ShiftError>) -> ShiftError {
+  errors.swap_remove(0)
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use crate::errors::{codes, ShiftError};
+use crate::expr::{Expr, FieldPatterns, MatchArm, Pattern, Stmt};
+
+// Avisos que não impedem o script de rodar. Hoje só confere se os
+// `match` sobre enums cobrem todas as variantes.
+pub fn check(statements: &[Stmt]) -> Vec<ShiftError> {
+  let mut enums = HashMap::new();
+  walk(statements, &mut |node| {
+    if let Node::Stmt(Stmt::Enum { name, variants }) = node {
+      let names = variants.iter().map(|variant| variant.name.lexeme.clone()).collect();
+      enums.insert(name.lexeme.clone(), names);
+    }
+  });
+
+  let mut warnings = Vec::new();
+  walk(statements, &mut |node| {
+    let (subject, arms) = match node {
+      Node::Stmt(Stmt::Match { subject, arms }) => (subject, arms),
+      Node::Expr(Expr::Match { subject, arms, .. }) => (subject.as_ref(), arms),
+      _ => return,
+    };
+
+    if let Some((enum_name, missing)) = missing_variants(arms, |name| enums.get(name).cloned())
+      && !missing.is_empty()
+    {
+      let list: Vec<String> = missing.iter().map(|variant| format!("'{}.{}'", enum_name, variant)).collect();
+      let message = match list.as_slice() {
+        [one] => format!("Non-exhaustive match: {} is not covered.", one),
+        [rest @ .., last] => format!("Non-exhaustive match: {} and {} are not covered.", rest.join(", "), last),
+        [] => unreachable!(),
+      };
+
+      warnings.push(
+        ShiftError::warning(codes::NON_EXHAUSTIVE_MATCH, subject.span(), message)
+          .with_help("add arms for them or a '_ => ...' arm"),
+      );
+    }
+  });
+
+  warnings
+}
+
+// Variantes do enum que nenhum braço cobre. `None` quando os padrões não
+// são de um enum conhecido. Braços com guarda ou com padrões dentro da
+// variante (`Shape.Circle(0)`) não contam, porque podem não casar.
+pub(crate) fn missing_variants(
+  arms: &[MatchArm],
+  variants_of: impl Fn(&str) -> Option<Vec<String>>,
+) -> Option<(String, Vec<String>)> {
+  let enum_name = arms.iter().find_map(|arm| match &arm.pattern {
+    Pattern::Variant { enum_name, .. } => Some(enum_name.lexeme.clone()),
+    _ => None,
+  })?;
+  let variants = variants_of(&enum_name)?;
+
+  let unguarded = || arms.iter().filter(|arm| arm.guard.is_none()).map(|arm| &arm.pattern);
+  if unguarded().any(Pattern::is_irrefutable) {
+    return Some((enum_name, Vec::new()));
+  }
+
+  let covered: Vec<&str> = unguarded().filter_map(|pattern| match pattern {
+    Pattern::Variant { enum_name: name, variant, fields, .. } if name.lexeme == enum_name => {
+      let complete = match fields {
+        FieldPatterns::Unit => true,
+        FieldPatterns::Tuple(patterns) => patterns.iter().all(Pattern::is_irrefutable),
+        FieldPatterns::Named(fields) => fields.iter().all(|(_, pattern)| pattern.is_irrefutable()),
+      };
+      complete.then_some(variant.lexeme.as_str())
+    },
+    _ => None,
+  }).collect();
+
+  let missing = variants.into_iter().filter(|variant| !covered.contains(&variant.as_str())).collect();
+  Some((enum_name, missing))
+}
+
+pub(crate) enum Node<'a> {
+  Stmt(&'a Stmt),
+  Expr(&'a Expr),
+}
+
+// Visita todas as instruções e expressões, inclusive dentro de funções
+pub(crate) fn walk<'a>(statements: &'a [Stmt], visit: &mut impl FnMut(Node<'a>)) {
+  for statement in statements {
+    walk_stmt(statement, visit);
+  }
+}
+
+fn walk_stmt<'a>(stmt: &'a Stmt, visit: &mut impl FnMut(Node<'a>)) {
+  visit(Node::Stmt(stmt));
+
+  match stmt {
+    Stmt::Expression { expression } => walk_expr(expression, visit),
+    Stmt::Let { initializer, .. } => {
+      if let Some(initializer) = initializer {
+        walk_expr(initializer, visit);
+      }
+    },
+    Stmt::Const { initializer, .. } => walk_expr(initializer, visit),
+    Stmt::Block { statements } => walk(statements, visit),
+    Stmt::If { condition, then_branch, else_branch } => {
+      walk_expr(condition, visit);
+      walk_stmt(then_branch, visit);
+      if let Some(else_branch) = else_branch {
+        walk_stmt(else_branch, visit);
+      }
+    },
+    Stmt::While { condition, body } => {
+      walk_expr(condition, visit);
+      walk_stmt(body, visit);
+    },
+    Stmt::Loop { body } => walk_stmt(body, visit),
+    Stmt::For { start, end, body, .. } => {
+      walk_expr(start, visit);
+      walk_expr(end, visit);
+      walk_stmt(body, visit);
+    },
+    Stmt::Return { value } => {
+      if let Some(value) = value {
+        walk_expr(value, visit);
+      }
+    },
+    Stmt::Function { declaration } => walk(&declaration.body, visit),
+    Stmt::Impl { methods, .. } => {
+      for method in methods {
+        walk(&method.declaration.body, visit);
+      }
+    },
+    Stmt::Trait { methods, .. } => {
+      for trait_method in methods {
+        walk(&trait_method.method.declaration.body, visit);
+      }
+    },
+    Stmt::Match { subject, arms } => {
+      walk_expr(subject, visit);
+      for arm in arms {
+        if let Some(guard) = &arm.guard {
+          walk_expr(guard, visit);
+        }
+        walk_stmt(&arm.body, visit);
+      }
+    },
+    Stmt::Break | Stmt::Continue | Stmt::Struct { .. } | Stmt::Enum { .. } => {},
+  }
+}
+
+pub(crate) fn walk_expr<'a>(expr: &'a Expr, visit: &mut impl FnMut(Node<'a>)) {
+  visit(Node::Expr(expr));
+
+  if let Expr::Closure { declaration, .. } = expr {
+    walk(&declaration.body, visit);
+  }
+  for child in expr.children() {
+    walk_expr(child, visit);
+  }
+}
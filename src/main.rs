@@ -1,27 +1,21 @@
-mod scanner;
-mod token;
-mod errors;
-mod expr;
-mod parser;
-mod interpreter;
-
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 
-use crate::interpreter::Interpreter;
-use crate::scanner::Scanner;
-use crate::expr::{Expr, LiteralValue};
-use crate::token::{Token, TokenType};
-use crate::parser::Parser;
+use shift::diagnostics::Renderer;
+use shift::{ErrorKind, Interpreter, RuntimeValue, ShiftError};
 
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() > 2 {
+    if args.len() > 1 && args[1] == "build" {
+        build(&args[2..]);
+    } else if args.len() > 2 {
         println!("Usage: shift [script]");
+        println!("       shift build <script> [-o <output.rs>]");
         process::exit(64);
     }else if args.len() == 2 {
         run_file(&args[1]);
@@ -32,18 +26,48 @@ fn main() {
 
 fn run_file(path: &str){
     let source = fs::read_to_string(path).expect("Failed to read file");
-    let mut interpreter = Interpreter;
+    let mut interpreter = Interpreter::new();
 
-    run(&mut interpreter, &source);
+    let statements = match shift::parse(&source) {
+        Ok(statements) => statements,
+        Err(errors) => fail(&source, path, &errors),
+    };
+    report(&source, path, &shift::lint::check(&statements));
 
-    if errors::had_error() {
-        process::exit(65)
+    if let Err(error) = interpreter.interpret(&statements) {
+        fail(&source, path, &[error]);
     }
+}
+
+// shift build file.st -o out.rs
+fn build(args: &[String]) {
+    let (path, output) = match args {
+        [path] => (path, Path::new(path).with_extension("rs")),
+        [path, flag, output] if flag == "-o" => (path, PathBuf::from(output)),
+        _ => {
+            println!("Usage: shift build <script> [-o <output.rs>]");
+            process::exit(64);
+        }
+    };
 
+    let source = fs::read_to_string(path).expect("Failed to read file");
+
+    let statements = match shift::parse(&source) {
+        Ok(statements) => statements,
+        Err(errors) => fail(&source, path, &errors),
+    };
+    report(&source, path, &shift::lint::check(&statements));
+
+    match shift::codegen::generate(&statements) {
+        Ok(code) => {
+            fs::write(&output, code).expect("Failed to write output file");
+        },
+        Err(error) => fail(&source, path, &[error]),
+    }
 }
 
 fn run_prompt() {
-    let mut interpreter = Interpreter;
+    let mut interpreter = Interpreter::new();
 
     loop {
         print!("> ");
@@ -56,28 +80,32 @@ fn run_prompt() {
             break;
         }
 
-        run(&mut interpreter, &line);
-        errors::reset_error();      
+        let result = shift::parse(&line).and_then(|statements| {
+            report(&line, "<repl>", &shift::lint::check(&statements));
+            interpreter.interpret(&statements).map_err(|error| vec![error])
+        });
+
+        match result {
+            Ok(RuntimeValue::Null) => {},
+            Ok(value) => println!("{}", value),
+            Err(errors) => report(&line, "<repl>", &errors),
+        }
     }
 }
 
-fn run (interpreter: &mut Interpreter, source: &str) {
-    let mut scanner = Scanner::new(source);
-    let tokens = scanner.scan_tokens();
-    let mut parser = Parser::new(tokens.clone());
-
-    // DEBUG
-    // for token in &tokens {
-    //     println!("{:?}", token);
-    // }
+fn report(source: &str, path: &str, errors: &[ShiftError]) {
+    // Cores só quando o stderr é um terminal e NO_COLOR não está definido
+    let color = io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let renderer = Renderer::new(source, path).colored(color);
 
-    match parser.parse() {
-        Ok(expression) => {
-            interpreter.interpret(&expression);
-        },
-        Err(error) => {
-            eprintln!("{}", error);
-        }
+    for error in errors {
+        eprintln!("{}", renderer.render(error));
     }
 }
 
+fn fail(source: &str, path: &str, errors: &[ShiftError]) -> ! {
+    report(source, path, errors);
+
+    let runtime = errors.iter().any(|error| error.kind == ErrorKind::Runtime);
+    process::exit(if runtime { 70 } else { 65 });
+}
@@ -0,0 +1,29 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::interpreter::{Interpreter, RuntimeValue};
+
+// Funções embutidas disponíveis em todo script
+pub fn register(interpreter: &mut Interpreter) {
+  interpreter.define_native("print", 1, |args| {
+    println!("{}", args[0]);
+    Ok(RuntimeValue::Null)
+  });
+
+  interpreter.define_native("len", 1, |args| {
+    match &args[0] {
+      RuntimeValue::String(s) => Ok(RuntimeValue::Integer(s.chars().count() as i64)),
+      RuntimeValue::Array(items) => Ok(RuntimeValue::Integer(items.borrow().len() as i64)),
+      RuntimeValue::Object(object) => Ok(RuntimeValue::Integer(object.borrow().len() as i64)),
+      other => Err(format!("len() expects a string, an array or an object, got '{}'.", other)),
+    }
+  });
+
+  // Segundos desde a época Unix, com fração
+  interpreter.define_native("clock", 0, |_args| {
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map_err(|error| error.to_string())?;
+
+    Ok(RuntimeValue::Float(now.as_secs_f64()))
+  });
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+use crate::interpreter::{display_once, RuntimeValue};
+
+// Objeto `{ chave: valor }`. As chaves ficam na ordem em que foram criadas,
+// então imprimir (ou serializar) um objeto sempre dá o mesmo resultado.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Object {
+  fields: Vec<(String, RuntimeValue)>,
+}
+
+impl Object {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn get(&self, key: &str) -> Option<&RuntimeValue> {
+    self.fields.iter().find(|(k, _)| k == key).map(|(_, value)| value)
+  }
+
+  // Chave repetida troca o valor mas mantém a posição original
+  pub fn set(&mut self, key: &str, value: RuntimeValue) {
+    match self.fields.iter_mut().find(|(k, _)| k == key) {
+      Some((_, slot)) => *slot = value,
+      None => self.fields.push((key.to_string(), value)),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.fields.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.fields.is_empty()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&str, &RuntimeValue)> {
+    self.fields.iter().map(|(key, value)| (key.as_str(), value))
+  }
+}
+
+impl fmt::Display for Object {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.fields.is_empty() {
+      return write!(f, "{{}}");
+    }
+
+    display_once(f, (self as *const Self).cast(), "{...}", |f| {
+      write!(f, "{{ ")?;
+      for (i, (key, value)) in self.fields.iter().enumerate() {
+        if i > 0 {
+          write!(f, ", ")?;
+        }
+        match value {
+          RuntimeValue::String(s) => write!(f, "{}: {:?}", key, s)?,
+          value => write!(f, "{}: {}", key, value)?,
+        }
+      }
+      write!(f, " }}")
+    })
+  }
+}
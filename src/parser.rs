@@ -1,29 +1,32 @@
-use core::fmt;
+use crate::errors::{codes, ShiftError};
+use crate::token::{Span, Token, TokenType};
+use std::rc::Rc;
 
-use crate::token::{Token, TokenType};
-use crate::expr::{Expr, LiteralValue};
+use crate::expr::{
+  Expr, Field, FieldPatterns, FunctionDecl, LiteralValue, MatchArm, Method, Param, Pattern, Property, Stmt, TraitMethod,
+  Variant, VariantFields,
+};
 
 #[derive(Debug, Clone)]
 pub struct Parser {
   tokens: Vec<Token>,
   current: usize,
+  loop_depth: usize,
+  function_depth: usize,
+  block_depth: usize,
+  // Em `if x {`, o '{' abre o bloco e não um `x { ... }`
+  no_struct_literal: bool,
+  // Na guarda de um braço, `x if ok => ...` não é uma closure `ok => ...`
+  no_closure: bool,
+  errors: Vec<ShiftError>,
 }
 
-// Isso deve ir em um arquivo separado posteriormente! ~ Brasga
+// AST parcial (sem as instruções que falharam) e todos os erros encontrados,
+// para ferramentas que precisam da árvore mesmo com código quebrado.
 #[derive(Debug, Clone)]
-pub struct ParserError {
-  pub token: Token,
-  pub message:String,
-}
-
-impl fmt::Display for ParserError {
-  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "[Line {}] Error at '{}': {}", 
-      self.token.line,
-      self.token.lexeme,
-      self.message
-    )
-  }
+pub struct ParseResult {
+  pub statements: Vec<Stmt>,
+  pub errors: Vec<ShiftError>,
 }
 
 impl Parser {
@@ -31,119 +34,1214 @@ impl Parser {
       Self {
         tokens,
         current: 0,
+        loop_depth: 0,
+        function_depth: 0,
+        block_depth: 0,
+        no_struct_literal: false,
+        no_closure: false,
+        errors: Vec::new(),
       }
     }
     
-    fn primary(&mut self) -> Result<Expr, ParserError> {
+    fn primary(&mut self) -> Result<Expr, ShiftError> {
       let token = self.peek();
 
       match token.token_type {
-        TokenType::False              => { self.advance(); Ok(Expr::Literal { value: LiteralValue::Boolean(false) })},
-        TokenType::True               => { self.advance(); Ok(Expr::Literal { value: LiteralValue::Boolean(true) })},
-        TokenType::Null               => { self.advance(); Ok(Expr::Literal { value: LiteralValue::Null })},
-        TokenType::Integer(i)    => { self.advance(); Ok(Expr::Literal { value: LiteralValue::Integer(i) })},
-        TokenType::Float(f)      => { self.advance(); Ok(Expr::Literal { value: LiteralValue::Float(f) })},
-        TokenType::String(s)  => { self.advance(); Ok(Expr::Literal { value: LiteralValue::String(s) })},
+        TokenType::False              => { let span = self.advance().span; Ok(Expr::Literal { value: LiteralValue::Boolean(false), span })},
+        TokenType::True               => { let span = self.advance().span; Ok(Expr::Literal { value: LiteralValue::Boolean(true), span })},
+        TokenType::Null               => { let span = self.advance().span; Ok(Expr::Literal { value: LiteralValue::Null, span })},
+        TokenType::Integer(i)    => { let span = self.advance().span; Ok(Expr::Literal { value: LiteralValue::Integer(i), span })},
+        TokenType::Float(f)      => { let span = self.advance().span; Ok(Expr::Literal { value: LiteralValue::Float(f), span })},
+        TokenType::String(s)  => { let span = self.advance().span; Ok(Expr::Literal { value: LiteralValue::String(s), span })},
+        TokenType::TemplatePart(_)    => self.template(),
+        TokenType::Identifier(_) if !self.no_closure && self.is_arrow_function() => self.closure(),
+        TokenType::Identifier(_) if self.is_struct_literal() => {
+          let name = self.advance();
+          let (properties, close) = self.properties()?;
+          Ok(Expr::StructLiteral { span: name.span.to(close.span), name, variant: None, properties })
+        },
+        TokenType::Identifier(_)      => { let name = self.advance(); Ok(Expr::Variable { span: name.span, name })},
 
-        TokenType::LeftParen => {
+        TokenType::LeftBracket => self.array(),
+        TokenType::Match => {
           self.advance();
-          let expr = self.expression()?;
-          self.consume(TokenType::RightParen, "Expect ')' after expression")?;
+          let (subject, arms, close) = self.match_arms(false)?;
+          Ok(Expr::Match { span: token.span.to(close.span), subject: Box::new(subject), arms })
+        },
+        // Instruções que começam com '{' são blocos, aqui só chegam expressões
+        TokenType::LeftBrace => self.object(),
+        TokenType::LeftParen if !self.no_closure && self.is_arrow_function() => self.closure(),
+        TokenType::LeftParen => {
+          let open = self.advance();
+          // Entre parênteses volta a valer: `if (User { .. }).ativo {`
+          let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+          let no_closure = std::mem::replace(&mut self.no_closure, false);
+          let expr = self.expression();
+          self.no_struct_literal = no_struct_literal;
+          self.no_closure = no_closure;
+          let expr = expr?;
+          let close = self.consume(TokenType::RightParen, "Expect ')' after expression")
+            .map_err(|error| error.with_label(open.span, "to match this '('"))?;
 
-          Ok(Expr::Grouping { expression: Box::new(expr) })
+          Ok(Expr::Grouping { expression: Box::new(expr), span: open.span.to(close.span) })
         },
         _ => {
           let token = self.peek();
-          Err(self.error(token, "Expect expression."))
+          Err(self.error(token, codes::EXPECTED_EXPRESSION, "Expect expression."))
+        }
+      }
+    }
+
+    fn array(&mut self) -> Result<Expr, ShiftError> {
+      let open = self.advance();
+      let mut elements = Vec::new();
+
+      // Os colchetes delimitam os elementos, como os parênteses no agrupamento
+      let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+      let no_closure = std::mem::replace(&mut self.no_closure, false);
+      let parsed = self.elements(&mut elements);
+      self.no_struct_literal = no_struct_literal;
+      self.no_closure = no_closure;
+      parsed?;
+
+      let close = self.consume(TokenType::RightBracket, "Expect ']' after array elements.")
+        .map_err(|error| error.with_label(open.span, "to match this '['"))?;
+
+      Ok(Expr::Array { elements, span: open.span.to(close.span) })
+    }
+
+    fn elements(&mut self, elements: &mut Vec<Expr>) -> Result<(), ShiftError> {
+      while !self.check(TokenType::RightBracket) && !self.is_at_end() {
+        if self.match_token(&[TokenType::Spread]) {
+          let spread = self.previous();
+          let expression = self.expression()?;
+          let span = spread.span.to(expression.span());
+          elements.push(Expr::Spread { expression: Box::new(expression), span });
+        } else {
+          elements.push(self.expression()?);
+        }
+
+        // Vírgula no final é permitida: [1, 2, 3,]
+        if !self.match_token(&[TokenType::Comma]) {
+          break;
+        }
+      }
+      Ok(())
+    }
+
+    fn object(&mut self) -> Result<Expr, ShiftError> {
+      let open = self.peek();
+      let (properties, close) = self.properties()?;
+
+      Ok(Expr::Object { properties, span: open.span.to(close.span) })
+    }
+
+    // Nome seguido de '{' na mesma linha, fora de condições
+    fn is_struct_literal(&self) -> bool {
+      let name = self.peek();
+
+      !self.no_struct_literal
+        && self.tokens.get(self.current + 1).is_some_and(|next| {
+          next.token_type == TokenType::LeftBrace && next.span.line == name.span.line
+        })
+    }
+
+    // O mesmo para o nome já consumido de uma variante
+    fn is_struct_literal_after(&self, name: &Token) -> bool {
+      !self.no_struct_literal && self.check(TokenType::LeftBrace) && self.peek().span.line == name.span.line
+    }
+
+    // `{ chave: valor, atalho, ...outro }`, usado por objetos e structs.
+    // Devolve também o '}' para o span de quem chamou.
+    fn properties(&mut self) -> Result<(Vec<Property>, Token), ShiftError> {
+      let open = self.consume(TokenType::LeftBrace, "Expect '{'.")?;
+      let mut properties = Vec::new();
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        if self.match_token(&[TokenType::Spread]) {
+          properties.push(Property::Spread(self.expression()?));
+        } else {
+          let token = self.peek();
+          let key = if let TokenType::String(key) = &token.token_type {
+            self.advance();
+            key.clone()
+          } else {
+            self.property_name("Expect property name or string key.")?.lexeme
+          };
+
+          let value = if self.match_token(&[TokenType::Colon]) {
+            self.expression()?
+          } else if let TokenType::Identifier(_) = token.token_type {
+            // { total } é o mesmo que { total: total }
+            Expr::Variable { name: token.clone(), span: token.span }
+          } else {
+            let next = self.peek();
+            return Err(self.error(next, codes::EXPECTED_TOKEN, "Expect ':' after property key."));
+          };
+
+          properties.push(Property::Field { key, key_span: token.span, value });
+        }
+
+        if !self.match_token(&[TokenType::Comma]) {
+          break;
+        }
+      }
+
+      let close = self.consume(TokenType::RightBrace, "Expect '}' after object properties.")
+        .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+
+      Ok((properties, close))
+    }
+
+    // Nomes de propriedade aceitam palavras reservadas: obj.type, { match: 1 }
+    fn property_name(&mut self, message: &str) -> Result<Token, ShiftError> {
+      let token = self.peek();
+      let is_word = token.lexeme.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && !matches!(token.token_type, TokenType::TemplatePart(_) | TokenType::TemplateEnd(_));
+
+      if is_word {
+        Ok(self.advance())
+      } else {
+        Err(self.error(token, codes::EXPECTED_TOKEN, message))
+      }
+    }
+
+    // O scanner já separou os trechos de texto, aqui só intercalamos as expressões
+    fn template(&mut self) -> Result<Expr, ShiftError> {
+      let open = self.advance();
+      let mut parts = Vec::new();
+      let mut text = open.clone();
+
+      loop {
+        if let TokenType::TemplatePart(s) | TokenType::TemplateEnd(s) = &text.token_type
+          && !s.is_empty()
+        {
+          parts.push(Expr::Literal { value: LiteralValue::String(s.clone()), span: text.span });
+        }
+
+        if let TokenType::TemplateEnd(_) = text.token_type {
+          return Ok(Expr::Template { parts, span: open.span.to(text.span) });
         }
+
+        parts.push(self.expression()?);
+
+        let token = self.peek();
+        match token.token_type {
+          TokenType::TemplatePart(_) | TokenType::TemplateEnd(_) => text = self.advance(),
+          _ => {
+            return Err(self.error(token, codes::EXPECTED_TOKEN, "Expect '}' after interpolated expression.")
+              .with_label(text.span, "interpolation starts here"));
+          }
+        }
+      }
+    }
+
+    // `x => ...` ou `(a, b) => ...`: procura o ')' correspondente e olha se vem um '=>'
+    fn is_arrow_function(&self) -> bool {
+      if let TokenType::Identifier(_) = self.peek().token_type {
+        return self.tokens.get(self.current + 1).is_some_and(|t| t.token_type == TokenType::Arrow);
+      }
+
+      let mut depth = 0;
+      for (i, token) in self.tokens.iter().enumerate().skip(self.current) {
+        match token.token_type {
+          TokenType::LeftParen => depth += 1,
+          TokenType::RightParen => {
+            depth -= 1;
+            if depth == 0 {
+              return self.tokens.get(i + 1).is_some_and(|t| t.token_type == TokenType::Arrow);
+            }
+          },
+          TokenType::Eof => return false,
+          _ => {}
+        }
+      }
+
+      false
+    }
+
+    fn closure(&mut self) -> Result<Expr, ShiftError> {
+      let start = self.peek().span;
+      let params = if self.match_token(&[TokenType::LeftParen]) {
+        self.parameters()?
+      } else {
+        let name = self.consume_identifier("Expect parameter name.")?;
+        vec![Param { name, type_annotation: None }]
+      };
+      self.consume(TokenType::Arrow, "Expect '=>' after closure parameters.")?;
+
+      // Corpo em bloco ou uma única expressão que vira o retorno
+      let body = if self.check(TokenType::LeftBrace) {
+        self.function_body("Expect '{' before closure body.")?
+      } else {
+        let value = self.expression()?;
+        vec![Stmt::Return { value: Some(value) }]
+      };
+
+      let declaration = FunctionDecl { name: None, params, return_type: None, body };
+      let span = start.to(self.previous().span);
+      Ok(Expr::Closure { declaration: Rc::new(declaration), span })
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ShiftError> {
+      let open = self.previous();
+      let mut arguments = Vec::new();
+
+      // Argumentos podem ser closures e structs mesmo dentro de uma condição
+      let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+      let no_closure = std::mem::replace(&mut self.no_closure, false);
+      let parsed = self.arguments(&mut arguments);
+      self.no_struct_literal = no_struct_literal;
+      self.no_closure = no_closure;
+      parsed?;
+
+      let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")
+        .map_err(|error| error.with_label(open.span, "to match this '('"))?;
+
+      let span = callee.span().to(paren.span);
+      Ok(Expr::Call { callee: Box::new(callee), paren, arguments, span })
+    }
+
+    fn arguments(&mut self, arguments: &mut Vec<Expr>) -> Result<(), ShiftError> {
+      if !self.check(TokenType::RightParen) {
+        loop {
+          arguments.push(self.expression()?);
+          if !self.match_token(&[TokenType::Comma]) {
+            break;
+          }
+        }
+      }
+
+      Ok(())
+    }
+
+    fn call(&mut self) -> Result<Expr, ShiftError> {
+      let mut expr = self.primary()?;
+
+      loop {
+        if self.match_token(&[TokenType::LeftParen]) {
+          expr = self.finish_call(expr)?;
+        } else if self.check(TokenType::LeftBracket) && self.peek().span.line == self.previous().span.line {
+          // Como no `++`, um '[' na linha de baixo começa um literal, não um índice
+          let open = self.advance();
+          let index = self.expression()?;
+          let close = self.consume(TokenType::RightBracket, "Expect ']' after index.")
+            .map_err(|error| error.with_label(open.span, "to match this '['"))?;
+          let span = expr.span().to(close.span);
+          expr = Expr::Index { object: Box::new(expr), index: Box::new(index), span };
+        } else if self.match_token(&[TokenType::Dot, TokenType::QuestionDot]) {
+          let optional = self.previous().token_type == TokenType::QuestionDot;
+          let name = self.property_name("Expect property name after '.'.")?;
+
+          // Shape.Rect { w: 1, h: 2 }
+          if let Expr::Variable { name: enum_name, .. } = &expr
+            && !optional
+            && self.is_struct_literal_after(&name)
+          {
+            let enum_name = enum_name.clone();
+            let (properties, close) = self.properties()?;
+            let span = enum_name.span.to(close.span);
+            expr = Expr::StructLiteral { name: enum_name, variant: Some(name), properties, span };
+            continue;
+          }
+
+          let span = expr.span().to(name.span);
+          expr = Expr::Get { object: Box::new(expr), name, optional, span };
+        } else {
+          break;
+        }
+      }
+
+      // Sem ';' obrigatório, um `++` na linha de baixo começa outra instrução
+      let same_line = self.peek().span.line == self.previous().span.line;
+      if same_line && self.match_token(&[TokenType::Increment, TokenType::Decrement]) {
+        let operator: Token = self.previous();
+        self.check_assignable(&expr, &operator)?;
+        let span = expr.span().to(operator.span);
+        return Ok(Expr::Update { target: Box::new(expr), operator, prefix: false, span });
+      }
+
+      Ok(expr)
+    }
+
+    // Alvos de `=`, `+=` e `++`
+    fn check_assignable(&self, target: &Expr, operator: &Token) -> Result<(), ShiftError> {
+      match target {
+        Expr::Variable { .. } | Expr::Index { .. } | Expr::Get { optional: false, .. } => Ok(()),
+        _ => Err(self.error(operator.clone(), codes::INVALID_ASSIGNMENT_TARGET, &format!("Invalid target for '{}'.", operator.lexeme))
+          .with_label(target.span(), "this cannot be assigned to")),
+      }
+    }
+
+    // ** associa à direita e liga mais forte que o sinal: -2 ** 2 == -4
+    fn power(&mut self) -> Result<Expr, ShiftError> {
+      let expr = self.call()?;
+
+      if self.match_token(&[TokenType::StarStar]) {
+        let operator: Token = self.previous();
+        let right: Expr = self.unary()?;
+        let span = expr.span().to(right.span());
+        return Ok(Expr::Binary { left: Box::new(expr), operator, right: Box::new(right), span });
       }
+
+      Ok(expr)
     }
 
-    fn unary(&mut self) -> Result<Expr, ParserError> {
-      if self.match_token(&[TokenType::Bang, TokenType::Minus]) {
+    fn unary(&mut self) -> Result<Expr, ShiftError> {
+      if self.match_token(&[TokenType::Increment, TokenType::Decrement]) {
+        let operator: Token = self.previous();
+        let target: Expr = self.unary()?;
+        self.check_assignable(&target, &operator)?;
+        let span = operator.span.to(target.span());
+        return Ok(Expr::Update { target: Box::new(target), operator, prefix: true, span })
+      }
+
+      if self.match_token(&[TokenType::Bang, TokenType::Minus, TokenType::BitNot]) {
         let operator: Token = self.previous();
         let right: Expr = self.unary()?;
-        return Ok(Expr::Unary { operator, right: Box::new(right) })
+        let span = operator.span.to(right.span());
+        return Ok(Expr::Unary { operator, right: Box::new(right), span })
       }
 
-      self.primary()
+      self.power()
     }
 
-    fn factor(&mut self) -> Result<Expr, ParserError> {
+    fn factor(&mut self) -> Result<Expr, ShiftError> {
       let mut expr = self.unary()?;
 
-      while self.match_token(&[TokenType::Slash, TokenType::Star]) {
+      while self.match_token(&[TokenType::Slash, TokenType::Star, TokenType::Percent, TokenType::WrappingStar, TokenType::SaturatingStar]) {
         let operator: Token = self.previous();
         let right: Expr = self.unary()?;
+        let span = expr.span().to(right.span());
         expr = Expr::Binary { 
           left: Box::new(expr), 
           operator, 
-          right: Box::new(right) 
+          right: Box::new(right),
+          span,
         };
       }
 
       Ok(expr)
     }
 
-    fn term (&mut self) -> Result<Expr, ParserError> {
+    fn term (&mut self) -> Result<Expr, ShiftError> {
       let mut expr = self.factor()?;
 
-      while self.match_token(&[TokenType::Minus, TokenType::Plus]) {
+      while self.match_token(&[
+        TokenType::Minus, TokenType::Plus,
+        TokenType::WrappingMinus, TokenType::WrappingPlus,
+        TokenType::SaturatingMinus, TokenType::SaturatingPlus,
+      ]) {
         let operator: Token = self.previous();
         let right: Expr = self.factor()?;
+        let span = expr.span().to(right.span());
         expr = Expr::Binary { 
           left: Box::new(expr), 
           operator, 
-          right: Box::new(right) 
+          right: Box::new(right),
+          span,
         };
       }
 
       Ok(expr)
     }
 
-    fn comparison(&mut self) -> Result<Expr, ParserError>{
-      let mut expr = self.term()?;
+    // Níveis dos operadores de bits, do mais forte ao mais fraco (como no Rust):
+    // << >>, &, ^, |. Todos ficam acima das comparações.
+    fn shift(&mut self) -> Result<Expr, ShiftError> {
+      self.binary_level(&[TokenType::ShiftLeft, TokenType::ShiftRight], Self::term)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, ShiftError> {
+      self.binary_level(&[TokenType::BitAnd], Self::shift)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr, ShiftError> {
+      self.binary_level(&[TokenType::BitXor], Self::bit_and)
+    }
+
+    fn bit_or(&mut self) -> Result<Expr, ShiftError> {
+      self.binary_level(&[TokenType::OrBitwise], Self::bit_xor)
+    }
+
+    fn binary_level(
+      &mut self,
+      operators: &[TokenType],
+      operand: fn(&mut Self) -> Result<Expr, ShiftError>,
+    ) -> Result<Expr, ShiftError> {
+      let mut expr = operand(self)?;
+
+      while self.match_token(operators) {
+        let operator: Token = self.previous();
+        let right: Expr = operand(self)?;
+        let span = expr.span().to(right.span());
+        expr = Expr::Binary { left: Box::new(expr), operator, right: Box::new(right), span };
+      }
+
+      Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ShiftError>{
+      let mut expr = self.bit_or()?;
 
       while self.match_token(&[TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual]) {
         let operator: Token = self.previous();
-        let right: Expr = self.term()?;
+        let right: Expr = self.bit_or()?;
+        let span = expr.span().to(right.span());
         expr = Expr::Binary { 
           left: Box::new(expr), 
           operator, 
-          right: Box::new(right) 
+          right: Box::new(right),
+          span,
         };
       }
 
       Ok(expr)
     }
 
-    fn equality(&mut self) -> Result<Expr, ParserError>{
+    fn equality(&mut self) -> Result<Expr, ShiftError>{
       let mut expr = self.comparison()?;
 
       while self.match_token(&[TokenType::BangEqual, TokenType::EqualEqual]) {
         let operator: Token = self.previous();
         let right: Expr = self.comparison()?;
+        let span = expr.span().to(right.span());
         expr = Expr::Binary { 
           left: Box::new(expr), 
           operator, 
-          right: Box::new(right) 
+          right: Box::new(right),
+          span,
         };
       }
 
       Ok(expr)
     }
 
-    fn expression(&mut self) -> Result<Expr, ParserError> {
-      self.equality()
+    fn logic_and(&mut self) -> Result<Expr, ShiftError> {
+      let mut expr = self.equality()?;
+
+      while self.match_token(&[TokenType::And]) {
+        let operator: Token = self.previous();
+        let right: Expr = self.equality()?;
+        let span = expr.span().to(right.span());
+        expr = Expr::Logical { 
+          left: Box::new(expr), 
+          operator, 
+          right: Box::new(right),
+          span,
+        };
+      }
+
+      Ok(expr)
+    }
+
+    fn logic_or(&mut self) -> Result<Expr, ShiftError> {
+      let mut expr = self.logic_and()?;
+
+      while self.match_token(&[TokenType::OrLogical]) {
+        let operator: Token = self.previous();
+        let right: Expr = self.logic_and()?;
+        let span = expr.span().to(right.span());
+        expr = Expr::Logical { 
+          left: Box::new(expr), 
+          operator, 
+          right: Box::new(right),
+          span,
+        };
+      }
+
+      Ok(expr)
+    }
+
+    // a ?? b: b só é avaliado se a for null
+    fn coalesce(&mut self) -> Result<Expr, ShiftError> {
+      let mut expr = self.logic_or()?;
+
+      while self.match_token(&[TokenType::QuestionQuestion]) {
+        let operator: Token = self.previous();
+        let right: Expr = self.logic_or()?;
+        let span = expr.span().to(right.span());
+        expr = Expr::Logical { left: Box::new(expr), operator, right: Box::new(right), span };
+      }
+
+      Ok(expr)
+    }
+
+    // cond ? a : b, associa à direita: a ? b : c ? d : e
+    fn conditional(&mut self) -> Result<Expr, ShiftError> {
+      let condition = self.coalesce()?;
+
+      if self.match_token(&[TokenType::Question]) {
+        let question = self.previous();
+        let then_branch = self.expression()?;
+        self.consume(TokenType::Colon, "Expect ':' in conditional expression.")
+          .map_err(|error| error.with_label(question.span, "to match this '?'"))?;
+        let else_branch = self.conditional()?;
+
+        let span = condition.span().to(else_branch.span());
+        return Ok(Expr::Ternary {
+          condition: Box::new(condition),
+          then_branch: Box::new(then_branch),
+          else_branch: Box::new(else_branch),
+          span,
+        });
+      }
+
+      Ok(condition)
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ShiftError> {
+      let expr = self.conditional()?;
+
+      if self.match_token(&[TokenType::Equal]) {
+        let equals: Token = self.previous();
+        let value: Expr = self.assignment()?;
+
+        match expr {
+          Expr::Variable { name, span } => {
+            let span = span.to(value.span());
+            return Ok(Expr::Assign { name, value: Box::new(value), span });
+          },
+          Expr::Index { object, index, span } => {
+            let span = span.to(value.span());
+            return Ok(Expr::SetIndex { object, index, value: Box::new(value), span });
+          },
+          Expr::Get { object, name, optional: false, span } => {
+            let span = span.to(value.span());
+            return Ok(Expr::Set { object, name, value: Box::new(value), span });
+          },
+          _ => {},
+        }
+
+        return Err(self.error(equals, codes::INVALID_ASSIGNMENT_TARGET, "Invalid assignment target.")
+          .with_label(expr.span(), "this cannot be assigned to"));
+      }
+
+      if self.match_token(&[TokenType::PlusEqual, TokenType::MinusEqual, TokenType::StarEqual, TokenType::SlashEqual]) {
+        let operator: Token = self.previous();
+        self.check_assignable(&expr, &operator)?;
+        let value: Expr = self.assignment()?;
+        let span = expr.span().to(value.span());
+        return Ok(Expr::CompoundAssign { target: Box::new(expr), operator, value: Box::new(value), span });
+      }
+
+      Ok(expr)
+    }
+
+    fn expression(&mut self) -> Result<Expr, ShiftError> {
+      self.assignment()
+    }
+
+    // O `match` já foi consumido. Devolve também o '}' para o span.
+    // Só o `match` usado como instrução aceita blocos nos braços.
+    fn match_arms(&mut self, statement: bool) -> Result<(Expr, Vec<MatchArm>, Token), ShiftError> {
+      let subject = self.condition()?;
+      let open = self.consume(TokenType::LeftBrace, "Expect '{' after match value.")?;
+      let mut arms = Vec::new();
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        let pattern = self.pattern()?;
+        let guard = if self.match_token(&[TokenType::If]) { Some(self.guard()?) } else { None };
+        self.consume(TokenType::Arrow, "Expect '=>' after match pattern.")?;
+
+        let body = if statement && self.match_token(&[TokenType::LeftBrace]) {
+          Stmt::Block { statements: self.block()? }
+        } else {
+          Stmt::Expression { expression: self.expression()? }
+        };
+        let is_block = matches!(body, Stmt::Block { .. });
+        arms.push(MatchArm { pattern, guard, body });
+
+        // Como no resto da linguagem, a quebra de linha vale como separador
+        let same_line = self.peek().span.line == self.previous().span.line;
+        if !self.match_token(&[TokenType::Comma]) && !is_block && same_line && !self.check(TokenType::RightBrace) {
+          let token = self.peek();
+          return Err(self.error(token, codes::EXPECTED_TOKEN, "Expect ',' between match arms."));
+        }
+      }
+
+      let close = self.consume(TokenType::RightBrace, "Expect '}' after match arms.")
+        .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+
+      Ok((subject, arms, close))
     }
 
-    pub fn parse(&mut self) -> Result<Expr, ParserError> {
-      match self.expression() {
-          Ok(expr) => Ok(expr),
-          Err(err) => {
-              self.synchronize(); 
-              Err(err)
+    fn pattern(&mut self) -> Result<Pattern, ShiftError> {
+      let token = self.peek();
+
+      match token.token_type {
+        TokenType::Identifier(_) if token.lexeme == "_" => { self.advance(); Ok(Pattern::Wildcard(token.span)) },
+        TokenType::Identifier(_) if self.tokens.get(self.current + 1).is_some_and(|next| next.token_type == TokenType::Dot) => {
+          self.variant_pattern()
+        },
+        TokenType::Identifier(_) => Ok(Pattern::Binding(self.advance())),
+        TokenType::String(s) => { self.advance(); Ok(Pattern::Literal { value: LiteralValue::String(s), span: token.span }) },
+        TokenType::True => { self.advance(); Ok(Pattern::Literal { value: LiteralValue::Boolean(true), span: token.span }) },
+        TokenType::False => { self.advance(); Ok(Pattern::Literal { value: LiteralValue::Boolean(false), span: token.span }) },
+        TokenType::Null => { self.advance(); Ok(Pattern::Literal { value: LiteralValue::Null, span: token.span }) },
+        TokenType::Integer(_) | TokenType::Float(_) | TokenType::Minus => {
+          let (start, start_span) = self.number_pattern()?;
+          if !self.match_token(&[TokenType::Range]) {
+            return Ok(Pattern::Literal { value: start, span: start_span });
           }
+
+          let (end, end_span) = self.number_pattern()?;
+          Ok(Pattern::Range { start, end, span: start_span.to(end_span) })
+        },
+        _ => Err(self.error(token, codes::EXPECTED_EXPRESSION, "Expect pattern.")),
+      }
+    }
+
+    fn number_pattern(&mut self) -> Result<(LiteralValue, Span), ShiftError> {
+      let minus = self.match_token(&[TokenType::Minus]).then(|| self.previous());
+      let token = self.peek();
+
+      let value = match token.token_type {
+        TokenType::Integer(i) if minus.is_some() => LiteralValue::Integer(-i),
+        TokenType::Integer(i) => LiteralValue::Integer(i),
+        TokenType::Float(f) if minus.is_some() => LiteralValue::Float(-f),
+        TokenType::Float(f) => LiteralValue::Float(f),
+        _ => return Err(self.error(token, codes::EXPECTED_EXPRESSION, "Expect number in pattern.")),
+      };
+      self.advance();
+
+      let span = minus.map_or(token.span, |minus| minus.span.to(token.span));
+      Ok((value, span))
+    }
+
+    // Shape.Circle(r), Shape.Rect { w, h: 0 }, Shape.Empty
+    fn variant_pattern(&mut self) -> Result<Pattern, ShiftError> {
+      let enum_name = self.advance();
+      self.advance();
+      let variant = self.consume_identifier("Expect variant name after '.'.")?;
+
+      let (fields, end) = if self.match_token(&[TokenType::LeftParen]) {
+        let open = self.previous();
+        let mut patterns = Vec::new();
+        while !self.check(TokenType::RightParen) && !self.is_at_end() {
+          patterns.push(self.pattern()?);
+          if !self.match_token(&[TokenType::Comma]) {
+            break;
+          }
+        }
+        let close = self.consume(TokenType::RightParen, "Expect ')' after variant patterns.")
+          .map_err(|error| error.with_label(open.span, "to match this '('"))?;
+        (FieldPatterns::Tuple(patterns), close.span)
+      } else if self.match_token(&[TokenType::LeftBrace]) {
+        let open = self.previous();
+        let mut fields = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+          let name = self.consume_identifier("Expect field name.")?;
+          // `{ w }` é o mesmo que `{ w: w }`
+          let pattern = if self.match_token(&[TokenType::Colon]) { self.pattern()? } else { Pattern::Binding(name.clone()) };
+          fields.push((name, pattern));
+          if !self.match_token(&[TokenType::Comma]) {
+            break;
+          }
+        }
+        let close = self.consume(TokenType::RightBrace, "Expect '}' after variant fields.")
+          .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+        (FieldPatterns::Named(fields), close.span)
+      } else {
+        (FieldPatterns::Unit, variant.span)
+      };
+
+      Ok(Pattern::Variant { span: enum_name.span.to(end), enum_name, variant, fields })
+    }
+
+    fn match_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let (subject, arms, _) = self.match_arms(true)?;
+      self.end_statement();
+
+      Ok(Stmt::Match { subject, arms })
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let expression = self.expression()?;
+      self.end_statement();
+
+      Ok(Stmt::Expression { expression })
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, ShiftError> {
+      let mut statements = Vec::new();
+      self.block_depth += 1;
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        if let Some(stmt) = self.recovering_declaration() {
+          statements.push(stmt);
+        }
+      }
+
+      self.block_depth -= 1;
+      self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+      Ok(statements)
+    }
+
+    // Corpos de if/while/loop/for sempre usam chaves, então a condição não precisa de parênteses
+    fn block_statement(&mut self, message: &str) -> Result<Stmt, ShiftError> {
+      self.consume(TokenType::LeftBrace, message)?;
+      Ok(Stmt::Block { statements: self.block()? })
+    }
+
+    fn loop_body(&mut self, message: &str) -> Result<Stmt, ShiftError> {
+      self.loop_depth += 1;
+      let body = self.block_statement(message);
+      self.loop_depth -= 1;
+
+      body
+    }
+
+    // Guarda de um braço do `match`, que termina no '=>' do braço
+    fn guard(&mut self) -> Result<Expr, ShiftError> {
+      let no_closure = std::mem::replace(&mut self.no_closure, true);
+      let guard = self.expression();
+      self.no_closure = no_closure;
+      guard
+    }
+
+    // Expressão antes do '{' de um bloco
+    fn condition(&mut self) -> Result<Expr, ShiftError> {
+      let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, true);
+      let condition = self.expression();
+      self.no_struct_literal = no_struct_literal;
+      condition
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let condition = self.condition()?;
+      let then_branch = self.block_statement("Expect '{' after if condition.")?;
+
+      let else_branch = if self.match_token(&[TokenType::Else]) {
+        if self.match_token(&[TokenType::If]) {
+          Some(Box::new(self.if_statement()?))
+        } else {
+          Some(Box::new(self.block_statement("Expect '{' after 'else'.")?))
+        }
+      } else {
+        None
+      };
+
+      Ok(Stmt::If { condition, then_branch: Box::new(then_branch), else_branch })
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let condition = self.condition()?;
+      let body = self.loop_body("Expect '{' after while condition.")?;
+
+      Ok(Stmt::While { condition, body: Box::new(body) })
+    }
+
+    fn loop_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let body = self.loop_body("Expect '{' after 'loop'.")?;
+
+      Ok(Stmt::Loop { body: Box::new(body) })
+    }
+
+    fn for_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let variable = self.consume_identifier("Expect loop variable name after 'for'.")?;
+      self.consume(TokenType::In, "Expect 'in' after loop variable.")?;
+      let start = self.condition()?;
+      self.consume(TokenType::Range, "Expect '..' in for range.")?;
+      let end = self.condition()?;
+      let body = self.loop_body("Expect '{' after for range.")?;
+
+      Ok(Stmt::For { variable, start, end, body: Box::new(body) })
+    }
+
+    fn break_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let keyword = self.previous();
+
+      if self.loop_depth == 0 {
+        return Err(self.error(keyword, codes::OUTSIDE_LOOP, "Can't use 'break' outside of a loop.")
+          .with_help("'break' only works inside 'while', 'loop' and 'for' bodies"));
+      }
+      self.end_statement();
+
+      Ok(Stmt::Break)
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let keyword = self.previous();
+
+      if self.loop_depth == 0 {
+        return Err(self.error(keyword, codes::OUTSIDE_LOOP, "Can't use 'continue' outside of a loop.")
+          .with_help("'continue' only works inside 'while', 'loop' and 'for' bodies"));
+      }
+      self.end_statement();
+
+      Ok(Stmt::Continue)
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ShiftError> {
+      let keyword = self.previous();
+
+      if self.function_depth == 0 {
+        return Err(self.error(keyword, codes::OUTSIDE_FUNCTION, "Can't return from top-level code.")
+          .with_help("'return' can only be used inside a 'fn' or a closure"));
+      }
+
+      // O valor só pode começar na mesma linha do `return`
+      let same_line = self.peek().span.line == keyword.span.line;
+      let value = if !same_line || self.check(TokenType::Semicolon) || self.check(TokenType::RightBrace) {
+        None
+      } else {
+        Some(self.expression()?)
+      };
+      self.end_statement();
+
+      Ok(Stmt::Return { value })
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ShiftError> {
+      if self.match_token(&[TokenType::Return]) {
+        return self.return_statement();
+      }
+
+      if self.match_token(&[TokenType::If]) {
+        return self.if_statement();
+      }
+
+      if self.match_token(&[TokenType::While]) {
+        return self.while_statement();
+      }
+
+      if self.match_token(&[TokenType::Loop]) {
+        return self.loop_statement();
+      }
+
+      if self.match_token(&[TokenType::For]) {
+        return self.for_statement();
+      }
+
+      if self.match_token(&[TokenType::Match]) {
+        return self.match_statement();
+      }
+
+      if self.match_token(&[TokenType::Break]) {
+        return self.break_statement();
+      }
+
+      if self.match_token(&[TokenType::Continue]) {
+        return self.continue_statement();
+      }
+
+      if self.match_token(&[TokenType::LeftBrace]) {
+        return Ok(Stmt::Block { statements: self.block()? });
+      }
+
+      self.expression_statement()
+    }
+
+    // Lista de parâmetros depois do '(' já consumido
+    // `: tipo` opcional depois de nomes de variáveis e parâmetros
+    fn type_annotation(&mut self) -> Result<Option<Token>, ShiftError> {
+      if self.match_token(&[TokenType::Colon]) {
+        return Ok(Some(self.type_name("Expect type name after ':'.")?));
+      }
+
+      Ok(None)
+    }
+
+    // `int`, `int[]` ou `int?`: viram um único token com os colchetes e o
+    // `?` no nome
+    fn type_name(&mut self, message: &str) -> Result<Token, ShiftError> {
+      let mut name = self.consume_identifier(message)?;
+
+      loop {
+        let close = if self.match_token(&[TokenType::LeftBracket]) {
+          self.consume(TokenType::RightBracket, "Expect ']' in array type.")?
+        } else if self.check(TokenType::Question) {
+          self.advance()
+        } else {
+          return Ok(name);
+        };
+
+        let suffix = if close.token_type == TokenType::Question { "?" } else { "[]" };
+        name = Token::new(name.token_type.clone(), format!("{}{}", name.lexeme, suffix), name.span.to(close.span));
+      }
+    }
+
+    fn parameters(&mut self) -> Result<Vec<Param>, ShiftError> {
+      let mut params = Vec::new();
+
+      if !self.check(TokenType::RightParen) {
+        loop {
+          let name = self.consume_identifier("Expect parameter name.")?;
+          let type_annotation = self.type_annotation()?;
+          params.push(Param { name, type_annotation });
+          if !self.match_token(&[TokenType::Comma]) {
+            break;
+          }
+        }
+      }
+
+      self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+      Ok(params)
+    }
+
+    // Um corpo de função começa um novo contexto: `return` passa a ser válido
+    // e `break`/`continue` não enxergam os loops de fora.
+    fn function_body(&mut self, message: &str) -> Result<Vec<Stmt>, ShiftError> {
+      let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+      let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+      let no_closure = std::mem::replace(&mut self.no_closure, false);
+      self.function_depth += 1;
+
+      let body = self.consume(TokenType::LeftBrace, message).and_then(|_| self.block());
+
+      self.function_depth -= 1;
+      self.no_struct_literal = no_struct_literal;
+      self.no_closure = no_closure;
+      self.loop_depth = loop_depth;
+      body
+    }
+
+    fn function(&mut self) -> Result<Stmt, ShiftError> {
+      let (name, params, return_type) = self.signature()?;
+      let body = self.function_body("Expect '{' before function body.")?;
+
+      let declaration = FunctionDecl { name: Some(name), params, return_type, body };
+      Ok(Stmt::Function { declaration: Rc::new(declaration) })
+    }
+
+    // `nome(params) -> tipo`, sem o corpo
+    fn signature(&mut self) -> Result<(Token, Vec<Param>, Option<Token>), ShiftError> {
+      let name = self.consume_identifier("Expect function name.")?;
+      self.consume(TokenType::LeftParen, "Expect '(' after function name.")?;
+      let params = self.parameters()?;
+
+      let return_type = if self.match_token(&[TokenType::SimpleArrow]) {
+        Some(self.type_name("Expect return type after '->'.")?)
+      } else {
+        None
+      };
+
+      Ok((name, params, return_type))
+    }
+
+    fn let_declaration(&mut self) -> Result<Stmt, ShiftError> {
+      let name = self.consume_identifier("Expect variable name.")?;
+      let type_annotation = self.type_annotation()?;
+
+      let initializer = if self.match_token(&[TokenType::Equal]) {
+        Some(self.expression()?)
+      } else {
+        None
+      };
+      self.end_statement();
+
+      Ok(Stmt::Let { name, type_annotation, initializer })
+    }
+
+    fn const_declaration(&mut self) -> Result<Stmt, ShiftError> {
+      let name = self.consume_identifier("Expect constant name.")?;
+      let type_annotation = self.type_annotation()?;
+      self.consume(TokenType::Equal, "Expect '=' after constant name, constants must be initialized.")?;
+      let initializer = self.expression()?;
+      self.end_statement();
+
+      Ok(Stmt::Const { name, type_annotation, initializer })
+    }
+
+    // struct User { name: string, age: int }
+    fn struct_declaration(&mut self) -> Result<Stmt, ShiftError> {
+      let name = self.consume_identifier("Expect struct name.")?;
+      let open = self.consume(TokenType::LeftBrace, "Expect '{' after struct name.")?;
+      let fields = self.fields(open)?;
+
+      Ok(Stmt::Struct { name, fields })
+    }
+
+    // `campo: tipo, ...}` depois do '{' já consumido
+    fn fields(&mut self, open: Token) -> Result<Vec<Field>, ShiftError> {
+      let mut fields = Vec::new();
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        let field = self.consume_identifier("Expect field name.")?;
+        let type_annotation = self.type_annotation()?;
+        fields.push(Field { name: field, type_annotation });
+
+        if !self.match_token(&[TokenType::Comma]) {
+          break;
+        }
+      }
+
+      self.consume(TokenType::RightBrace, "Expect '}' after fields.")
+        .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+
+      Ok(fields)
+    }
+
+    // enum Shape { Circle(float), Rect { w: float, h: float }, Empty }
+    fn enum_declaration(&mut self) -> Result<Stmt, ShiftError> {
+      let name = self.consume_identifier("Expect enum name.")?;
+      let open = self.consume(TokenType::LeftBrace, "Expect '{' after enum name.")?;
+      let mut variants = Vec::new();
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        let variant = self.consume_identifier("Expect variant name.")?;
+
+        let fields = if self.match_token(&[TokenType::LeftParen]) {
+          let paren = self.previous();
+          let mut types = Vec::new();
+          while !self.check(TokenType::RightParen) && !self.is_at_end() {
+            types.push(self.type_name("Expect type name.")?);
+            if !self.match_token(&[TokenType::Comma]) {
+              break;
+            }
+          }
+          self.consume(TokenType::RightParen, "Expect ')' after variant types.")
+            .map_err(|error| error.with_label(paren.span, "to match this '('"))?;
+          VariantFields::Tuple(types)
+        } else if self.match_token(&[TokenType::LeftBrace]) {
+          let brace = self.previous();
+          VariantFields::Named(self.fields(brace)?)
+        } else {
+          VariantFields::Unit
+        };
+
+        variants.push(Variant { name: variant, fields });
+
+        if !self.match_token(&[TokenType::Comma]) {
+          break;
+        }
+      }
+
+      self.consume(TokenType::RightBrace, "Expect '}' after enum variants.")
+        .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+
+      Ok(Stmt::Enum { name, variants })
+    }
+
+    // impl User { fn greet(self) { ... } fn new() -> User { ... } }
+    fn impl_declaration(&mut self) -> Result<Stmt, ShiftError> {
+      let mut name = self.consume_identifier("Expect struct name after 'impl'.")?;
+      let mut trait_name = None;
+      if self.match_token(&[TokenType::For]) {
+        trait_name = Some(name);
+        name = self.consume_identifier("Expect struct name after 'for'.")?;
+      }
+
+      let open = self.consume(TokenType::LeftBrace, "Expect '{' after impl name.")?;
+      let mut methods = Vec::new();
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        self.consume(TokenType::Fn, "Expect 'fn' before method.")?;
+        methods.push(self.method()?);
+      }
+
+      self.consume(TokenType::RightBrace, "Expect '}' after impl block.")
+        .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+
+      Ok(Stmt::Impl { name, trait_name, methods })
+    }
+
+    fn method(&mut self) -> Result<Method, ShiftError> {
+      let Stmt::Function { declaration } = self.function()? else {
+        unreachable!()
+      };
+
+      Ok(without_self(Rc::unwrap_or_clone(declaration)))
+    }
+
+    // trait Display { fn show(self) -> string }
+    fn trait_declaration(&mut self) -> Result<Stmt, ShiftError> {
+      let name = self.consume_identifier("Expect trait name.")?;
+      let open = self.consume(TokenType::LeftBrace, "Expect '{' after trait name.")?;
+      let mut methods = Vec::new();
+
+      while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+        self.consume(TokenType::Fn, "Expect 'fn' before method.")?;
+        methods.push(self.trait_method()?);
+      }
+
+      self.consume(TokenType::RightBrace, "Expect '}' after trait body.")
+        .map_err(|error| error.with_label(open.span, "to match this '{'"))?;
+
+      Ok(Stmt::Trait { name, methods })
+    }
+
+    // O corpo é opcional: com ele, o método vira o padrão dos `impl`
+    fn trait_method(&mut self) -> Result<TraitMethod, ShiftError> {
+      let (name, params, return_type) = self.signature()?;
+
+      let has_default = self.check(TokenType::LeftBrace);
+      let body = if has_default {
+        self.function_body("Expect '{' before method body.")?
+      } else {
+        self.end_statement();
+        Vec::new()
+      };
+
+      let declaration = FunctionDecl { name: Some(name), params, return_type, body };
+      Ok(TraitMethod { method: without_self(declaration), has_default })
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, ShiftError> {
+      if self.match_token(&[TokenType::Fn]) {
+        return self.function();
+      }
+
+      if self.match_token(&[TokenType::Let]) {
+        return self.let_declaration();
+      }
+
+      if self.match_token(&[TokenType::Const]) {
+        return self.const_declaration();
+      }
+
+      if self.match_token(&[TokenType::Struct]) {
+        return self.struct_declaration();
+      }
+
+      if self.match_token(&[TokenType::Impl]) {
+        return self.impl_declaration();
+      }
+
+      if self.match_token(&[TokenType::Enum]) {
+        return self.enum_declaration();
+      }
+
+      if self.match_token(&[TokenType::Trait]) {
+        return self.trait_declaration();
+      }
+
+      self.statement()
+    }
+
+    // Guarda o erro e pula até a próxima instrução, para continuar
+    // encontrando os outros erros do arquivo
+    fn recovering_declaration(&mut self) -> Option<Stmt> {
+      match self.declaration() {
+        Ok(stmt) => Some(stmt),
+        Err(err) => {
+          self.errors.push(err);
+          self.synchronize();
+          None
+        }
+      }
+    }
+
+    pub fn parse_partial(&mut self) -> ParseResult {
+      let mut statements = Vec::new();
+
+      while !self.is_at_end() {
+        if let Some(stmt) = self.recovering_declaration() {
+          statements.push(stmt);
+        }
+      }
+
+      ParseResult { statements, errors: std::mem::take(&mut self.errors) }
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ShiftError>> {
+      let result = self.parse_partial();
+
+      if result.errors.is_empty() {
+        Ok(result.statements)
+      } else {
+        Err(result.errors)
       }
     }
 
@@ -188,21 +1286,40 @@ impl Parser {
         self.peek().token_type == TokenType::Eof
     }
 
-    fn error(&self, token: Token, message: &str) -> ParserError {
-      ParserError { token, message: message.to_string(), }
+    fn error(&self, token: Token, code: &'static str, message: &str) -> ShiftError {
+      ShiftError::parse(code, token.span, message)
     }
 
-    fn consume(&mut self, type_: TokenType, message: &str) -> Result<Token, ParserError> {
+    fn consume(&mut self, type_: TokenType, message: &str) -> Result<Token, ShiftError> {
       if self.check(type_) {
           Ok(self.advance())
       } else {
           let token = self.peek();
-          Err(self.error(token, message))
+          Err(self.error(token, codes::EXPECTED_TOKEN, message))
       }
 
     }
 
+    fn consume_identifier(&mut self, message: &str) -> Result<Token, ShiftError> {
+      if let TokenType::Identifier(_) = self.peek().token_type {
+        Ok(self.advance())
+      } else {
+        let token = self.peek();
+        Err(self.error(token, codes::EXPECTED_TOKEN, message))
+      }
+    }
+
+    // O ';' é opcional no final de uma instrução
+    fn end_statement(&mut self) {
+      self.match_token(&[TokenType::Semicolon]);
+    }
+
     fn synchronize(&mut self) {
+      // Dentro de um bloco, o '}' fica para o bloco fechar normalmente
+      if self.block_depth > 0 && self.check(TokenType::RightBrace) {
+        return;
+      }
+
       self.advance();
 
       while !self.is_at_end() {
@@ -210,6 +1327,15 @@ impl Parser {
           return;
         }
 
+        // O ';' é opcional, então uma nova linha também encerra a instrução
+        if self.peek().span.line > self.previous().span.line {
+          return;
+        }
+
+        if self.block_depth > 0 && self.check(TokenType::RightBrace) {
+          return;
+        }
+
         match self.peek().token_type {
             TokenType::Import 
           | TokenType::Export
@@ -226,6 +1352,7 @@ impl Parser {
           | TokenType::Let
           | TokenType::Const
           | TokenType::Struct
+          | TokenType::Impl
           | TokenType::Component
           | TokenType::Server
           | TokenType::Client => return,
@@ -237,4 +1364,14 @@ impl Parser {
       }
     }
 
-}
\ No newline at end of file
+}
+
+// O `self` sai de `params` e vira `has_self`
+fn without_self(mut declaration: FunctionDecl) -> Method {
+  let has_self = declaration.params.first().is_some_and(|param| param.name.lexeme == "self");
+  if has_self {
+    declaration.params.remove(0);
+  }
+
+  Method { declaration: Rc::new(declaration), has_self }
+}
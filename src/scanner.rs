@@ -1,4 +1,5 @@
-use crate::{errors, token::{Token, TokenType}};
+use crate::errors::{codes, ShiftError};
+use crate::token::{Span, Token, TokenType};
 
 #[derive(Debug, Clone)]
 pub struct Scanner {
@@ -7,6 +8,17 @@ pub struct Scanner {
   start: usize,
   current: usize,
   line: usize,
+  // Índice do primeiro caractere da linha atual, para calcular a coluna
+  line_start: usize,
+  // Offset em bytes de `current`, já que `source` guarda chars
+  byte: usize,
+  // Posição de início do token atual
+  start_byte: usize,
+  start_line: usize,
+  start_column: usize,
+  errors: Vec<ShiftError>,
+  // Um item por `${` aberto: onde começou e quantas chaves estão abertas dentro dele
+  interpolations: Vec<(Span, usize)>,
 }
 
 impl Scanner {
@@ -17,22 +29,46 @@ impl Scanner {
       start: 0,
       current: 0,
       line: 1,
+      line_start: 0,
+      byte: 0,
+      start_byte: 0,
+      start_line: 1,
+      start_column: 1,
+      errors: Vec::new(),
+      interpolations: Vec::new(),
     }
   }
 
-  pub fn scan_tokens(&mut self) -> Vec<Token> {
+  // Lê o código inteiro e devolve os tokens, ou todos os erros encontrados
+  pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Vec<ShiftError>> {
         while !self.is_at_end() {
           self.start = self.current;
+          self.start_byte = self.byte;
+          self.start_line = self.line;
+          self.start_column = self.current - self.line_start + 1;
           self.scan_token();
         }
 
-      self.tokens.push(Token::new(
-        TokenType::Eof,
-        String::new(),
-        self.line
-      ));
+      for (span, _) in std::mem::take(&mut self.interpolations) {
+        self.errors.push(
+          ShiftError::lex(codes::UNTERMINATED_STRING, span, "Unterminated string interpolation.")
+            .with_help("close the interpolation with '}' and the string with '\"'")
+        );
+      }
+
+      let eof = Span {
+        start: self.byte,
+        end: self.byte,
+        line: self.line,
+        column: self.current - self.line_start + 1,
+      };
+      self.tokens.push(Token::new(TokenType::Eof, String::new(), eof));
 
-      self.tokens.clone()
+      if !self.errors.is_empty() {
+        return Err(self.errors.clone());
+      }
+
+      Ok(self.tokens.clone())
   }
 
   //lexes
@@ -42,62 +78,79 @@ impl Scanner {
     match c {
         '(' => self.add_token(TokenType::LeftParen),
         ')' => self.add_token(TokenType::RightParen),
-        '{' => self.add_token(TokenType::LeftBrace),
-        '}' => self.add_token(TokenType::RightBrace),
+        '{' => {
+          if let Some((_, depth)) = self.interpolations.last_mut() { *depth += 1; }
+          self.add_token(TokenType::LeftBrace)
+        },
+        '}' => {
+          match self.interpolations.last_mut() {
+            // Fecha o `${`, o resto é continuação da string
+            Some((_, 0)) => { self.interpolations.pop(); self.string(true); },
+            Some((_, depth)) => { *depth -= 1; self.add_token(TokenType::RightBrace) },
+            None => self.add_token(TokenType::RightBrace),
+          }
+        },
         '[' => self.add_token(TokenType::LeftBracket),
         ']' => self.add_token(TokenType::RightBracket),
         ',' => self.add_token(TokenType::Comma),
-        '?' => { self.add_token(TokenType::Question)},
+        '?' => { if self.match_char('?') { self.add_token(TokenType::QuestionQuestion); } else if self.match_char('.') { self.add_token(TokenType::QuestionDot); } else { self.add_token(TokenType::Question); }},
         ':' => self.add_token(TokenType::Colon),
         ';' => self.add_token(TokenType::Semicolon),
-        '*' => self.add_token(TokenType::Star),
+        '%' => self.add_token(TokenType::Percent),
+        '^' => self.add_token(TokenType::BitXor),
+        '~' => self.add_token(TokenType::BitNot),
+        '*' => { if self.match_char('*') { self.add_token(TokenType::StarStar); } else if self.match_char('=') { self.add_token(TokenType::StarEqual); } else if self.match_char('%') { self.add_token(TokenType::WrappingStar); } else if self.match_char('|') { self.add_token(TokenType::SaturatingStar); } else { self.add_token(TokenType::Star); }},
         '.' => { if self.match_char('.') { if self.match_char('.') { self.add_token(TokenType::Spread); } else { self.add_token(TokenType::Range); }} else { self.add_token(TokenType::Dot)}},
-        '+' => { if self.match_char('+') { self.add_token(TokenType::Increment); } else { self.add_token(TokenType::Plus); }},
-        '-' => { if self.match_char('-') { self.add_token(TokenType::Decrement);} else if self.match_char('>') { self.add_token(TokenType::SimpleArrow); } else { self.add_token(TokenType::Minus); } },
+        '+' => { if self.match_char('+') { self.add_token(TokenType::Increment); } else if self.match_char('=') { self.add_token(TokenType::PlusEqual); } else if self.match_char('%') { self.add_token(TokenType::WrappingPlus); } else if self.match_char('|') { self.add_token(TokenType::SaturatingPlus); } else { self.add_token(TokenType::Plus); }},
+        '-' => { if self.match_char('-') { self.add_token(TokenType::Decrement);} else if self.match_char('=') { self.add_token(TokenType::MinusEqual); } else if self.match_char('>') { self.add_token(TokenType::SimpleArrow); } else if self.match_char('%') { self.add_token(TokenType::WrappingMinus); } else if self.match_char('|') { self.add_token(TokenType::SaturatingMinus); } else { self.add_token(TokenType::Minus); } },
         '!' => { if self.match_char('=') { self.add_token(TokenType::BangEqual); } else { self.add_token(TokenType::Bang); }},
         '=' => { if self.match_char('=') { self.add_token(TokenType::EqualEqual); } else if self.match_char('>') { self.add_token(TokenType::Arrow); } else { self.add_token(TokenType::Equal); }},
-        '<' => { if self.match_char('=') { self.add_token(TokenType::LessEqual); } else { self.add_token(TokenType::Less); }},
-        '>' => { if self.match_char('=') { self.add_token(TokenType::GreaterEqual); } else { self.add_token(TokenType::Greater); }},
-        '/' => { if self.match_char('/') { while self.peek() != '\n' && !self.is_at_end() { self.advance(); } } else if self.match_char('*') { self.scan_multiline_comment(); } else { self.add_token(TokenType::Slash); }},
+        '<' => { if self.match_char('=') { self.add_token(TokenType::LessEqual); } else if self.match_char('<') { self.add_token(TokenType::ShiftLeft); } else { self.add_token(TokenType::Less); }},
+        '>' => { if self.match_char('=') { self.add_token(TokenType::GreaterEqual); } else if self.match_char('>') { self.add_token(TokenType::ShiftRight); } else { self.add_token(TokenType::Greater); }},
+        '/' => { if self.match_char('/') { while self.peek() != '\n' && !self.is_at_end() { self.advance(); } } else if self.match_char('*') { self.scan_multiline_comment(); } else if self.match_char('=') { self.add_token(TokenType::SlashEqual); } else { self.add_token(TokenType::Slash); }},
         '&' => { if self.match_char('&') { self.add_token(TokenType::And);} else { self.add_token(TokenType::BitAnd); }},
         '|' => { if self.match_char('|') { self.add_token(TokenType::OrLogical);} else { self.add_token(TokenType::OrBitwise); }},
         
         ' ' | '\r' | '\t' => {},
-        '\n' => { self.line += 1; },
-        '"' => { self.string(); },
+        '\n' => {},
+        '"' => { self.string(false); },
         _ => {
           if c.is_ascii_digit() { 
             self.number();
           } else if c.is_alphabetic() || c == '_' {
             self.identifier();
           } else { 
-            errors::error(self.line, "Unexpected character.") 
+            self.error(codes::UNEXPECTED_CHARACTER, "Unexpected character.")
           }
         },  
     }
   }
 
   fn scan_multiline_comment(&mut self) {
-    let mut nest_level = 1;
+    // Onde cada /* ainda aberto começou, para apontar o erro no lugar certo
+    let mut open = vec![self.span()];
     
-    while nest_level > 0 && !self.is_at_end() {
+    while !open.is_empty() && !self.is_at_end() {
         if self.peek() == '/' && self.peek_next() == '*' {
             // Achou /* aninhado
+            let start = self.position();
             self.advance(); self.advance();
-            nest_level += 1;
+            open.push(self.span_from(start));
         } else if self.peek() == '*' && self.peek_next() == '/' {
             // Achou */ fechando
             self.advance(); self.advance();
-            nest_level -= 1;
+            open.pop();
         } else {
-            if self.peek() == '\n' {
-                self.line += 1;
-            }
             self.advance();
         }
     }
     
-    if nest_level > 0 {
+    if let Some((outer, nested)) = open.split_first() {
+      let mut error = ShiftError::lex(codes::UNTERMINATED_COMMENT, *outer, "Unterminated block comment.");
+      for span in nested {
+        error = error.with_label(*span, "nested comment opened here is never closed");
+      }
+      self.errors.push(error.with_help("close each '/*' with a matching '*/'"));
     }
   }
 
@@ -105,9 +158,17 @@ impl Scanner {
     self.current >= self.source.len()
   }
 
+  // Toda quebra de linha passa por aqui, então é aqui que a linha avança
   fn advance(&mut self) -> char {
     let c = self.source[self.current];
     self.current += 1;
+    self.byte += c.len_utf8();
+
+    if c == '\n' {
+      self.line += 1;
+      self.line_start = self.current;
+    }
+
     c
   }
 
@@ -119,8 +180,8 @@ impl Scanner {
       return false
     }
 
-    self.current += 1;
-    return true
+    self.advance();
+    true
   }
 
   fn peek(&self) -> char {
@@ -147,11 +208,17 @@ impl Scanner {
       "return"         => TokenType::Return,
       "true"           => TokenType::True,
       "false"          => TokenType::False,
+      "null"           => TokenType::Null,
       "let"            => TokenType::Let,
       "const"          => TokenType::Const,
+      "struct"         => TokenType::Struct,
+      "impl"           => TokenType::Impl,
       "loop"           => TokenType::Loop,
       "while"          => TokenType::While,
       "for"            => TokenType::For,
+      "in"             => TokenType::In,
+      "break"          => TokenType::Break,
+      "continue"       => TokenType::Continue,
       "enum"           => TokenType::Enum,
       "type"           => TokenType::Type,
       "trait"          => TokenType::Trait, 
@@ -176,50 +243,199 @@ impl Scanner {
     self.add_token(token_type);
   }
 
-  fn string (&mut self) {
+  // `continuation` é o trecho depois de um `${...}`, que termina a template
+  fn string (&mut self, continuation: bool) {
+    let mut text = String::new();
+
     while self.peek() != '"' && !self.is_at_end() {
-      if self.peek() == '\n' { self.line += 1; }
-      self.advance();
+      if self.peek() == '$' && self.peek_next() == '{' {
+        let start = self.position();
+        self.advance(); self.advance();
+        self.interpolations.push((self.span_from(start), 0));
+        self.add_token(TokenType::TemplatePart(text));
+        return;
+      }
+
+      if self.peek() == '\\' {
+        if let Some(c) = self.escape() {
+          text.push(c);
+        }
+      } else {
+        text.push(self.advance());
+      }
     }
 
     if self.is_at_end() {
-      errors::error(self.line, "Unterminated string.");
+      self.error(codes::UNTERMINATED_STRING, "Unterminated string.");
       return;
     }
 
     self.advance();
 
-    let text: String = self.source[(self.start + 1)..self.current]
-        .iter()
-        .collect();
-    self.add_token(TokenType::String(text));
+    if continuation {
+      self.add_token(TokenType::TemplateEnd(text));
+    } else {
+      self.add_token(TokenType::String(text));
+    }
+  }
+
+  // Lê uma sequência que começa com '\'. Em caso de erro registra e segue
+  // lendo a string, para mostrar todos os escapes inválidos de uma vez.
+  fn escape(&mut self) -> Option<char> {
+    let start = self.position();
+    self.advance();
+
+    if self.is_at_end() {
+      return None;
+    }
+
+    match self.advance() {
+      'n' => Some('\n'),
+      't' => Some('\t'),
+      '"' => Some('"'),
+      '\\' => Some('\\'),
+      '$' => Some('$'),
+      'u' => self.unicode_escape(start),
+      other => {
+        let span = self.span_from(start);
+        self.errors.push(
+          ShiftError::lex(codes::INVALID_ESCAPE, span, format!("Unknown escape sequence '\\{}'.", other))
+            .with_help("valid escapes are \\n, \\t, \\\", \\\\, \\$ and \\u{...}")
+        );
+        None
+      },
+    }
+  }
+
+  // \u{1F600}: de 1 a 6 dígitos hexadecimais
+  fn unicode_escape(&mut self, start: (usize, usize, usize)) -> Option<char> {
+    let mut digits = String::new();
+    let braced = self.match_char('{');
+
+    if braced {
+      while self.peek().is_ascii_hexdigit() {
+        digits.push(self.advance());
+      }
+    }
+
+    let closed = braced && self.match_char('}');
+    let value = if closed && (1..=6).contains(&digits.len()) {
+      u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32)
+    } else {
+      None
+    };
+
+    if value.is_none() {
+      let span = self.span_from(start);
+      let message = if closed {
+        format!("'{}' is not a valid unicode code point.", digits)
+      } else {
+        "Malformed unicode escape.".to_string()
+      };
+      self.errors.push(
+        ShiftError::lex(codes::INVALID_ESCAPE, span, message)
+          .with_help("write unicode escapes as \\u{...} with 1 to 6 hex digits")
+      );
+    }
+
+    value
   }
 
   fn number(&mut self) {
-    while self.peek().is_ascii_digit() {
-      self.advance();
+    if self.source[self.start] == '0' {
+      let radix = match self.peek() {
+        'x' | 'X' => Some((16, "hexadecimal")),
+        'b' | 'B' => Some((2, "binary")),
+        'o' | 'O' => Some((8, "octal")),
+        _ => None,
+      };
+
+      if let Some((radix, name)) = radix {
+        self.advance();
+        self.radix_number(radix, name);
+        return;
+      }
     }
 
+    self.digits();
+    let mut is_float = false;
+
     if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+      is_float = true;
       self.advance();
+      self.digits();
+    }
 
-      while self.peek().is_ascii_digit() {
+    // 1e9, 2.5e-3
+    let sign = matches!(self.peek_next(), '+' | '-');
+    let exponent_digit = if sign { self.peek_at(2) } else { self.peek_next() };
+    if matches!(self.peek(), 'e' | 'E') && exponent_digit.is_ascii_digit() {
+      is_float = true;
+      self.advance();
+      if sign {
         self.advance();
       }
+      self.digits();
     }
 
-   
-    let text: String = self.source[self.start..self.current].iter().collect();
+    let text: String = self.source[self.start..self.current].iter().filter(|c| **c != '_').collect();
 
-    if text.contains('.') {
-      let value = text.parse::<f64>().unwrap_or(0.0);
-      self.add_token(TokenType::Float(value));
+    if is_float {
+      match text.parse::<f64>() {
+        Ok(value) if value.is_finite() => self.add_token(TokenType::Float(value)),
+        _ => self.error(codes::NUMBER_OUT_OF_RANGE, "Float literal is out of range."),
+      }
     } else {
-      let value = text.parse::<i64>().unwrap_or(0);
-      self.add_token(TokenType::Integer(value));
+      match text.parse::<i64>() {
+        Ok(value) => self.add_token(TokenType::Integer(value)),
+        Err(_) => self.out_of_range(),
+      }
     }
   }
 
+  // 0xFF, 0b1010, 0o17
+  fn radix_number(&mut self, radix: u32, name: &str) {
+    let prefix = self.current;
+    while self.peek().is_alphanumeric() || self.peek() == '_' {
+      self.advance();
+    }
+
+    let digits: String = self.source[prefix..self.current].iter().filter(|c| **c != '_').collect();
+
+    if digits.is_empty() {
+      self.error(codes::INVALID_NUMBER, &format!("Missing digits in {} literal.", name));
+      return;
+    }
+
+    if let Some(invalid) = digits.chars().find(|c| !c.is_digit(radix)) {
+      self.error(codes::INVALID_NUMBER, &format!("Invalid digit '{}' in {} literal.", invalid, name));
+      return;
+    }
+
+    match i64::from_str_radix(&digits, radix) {
+      Ok(value) => self.add_token(TokenType::Integer(value)),
+      Err(_) => self.out_of_range(),
+    }
+  }
+
+  // Dígitos decimais, com '_' opcional como separador: 1_000_000
+  fn digits(&mut self) {
+    while self.peek().is_ascii_digit() || self.peek() == '_' {
+      self.advance();
+    }
+  }
+
+  fn out_of_range(&mut self) {
+    self.errors.push(
+      ShiftError::lex(codes::NUMBER_OUT_OF_RANGE, self.span(), "Integer literal is out of range.")
+        .with_help(format!("integers are 64-bit, the largest is {}", i64::MAX))
+    );
+  }
+
+  fn peek_at(&self, offset: usize) -> char {
+    self.source.get(self.current + offset).copied().unwrap_or('\0')
+  }
+
   fn peek_next(&self) -> char {
     if self.current + 1 >= self.source.len() {
       return '\0'
@@ -227,16 +443,36 @@ impl Scanner {
     self.source[self.current + 1]
   }
 
+  fn error(&mut self, code: &'static str, message: &str) {
+    self.errors.push(ShiftError::lex(code, self.span(), message));
+  }
+
+  // Trecho do token atual, do início até o caractere corrente
+  fn span(&self) -> Span {
+    Span {
+      start: self.start_byte,
+      end: self.byte,
+      line: self.start_line,
+      column: self.start_column,
+    }
+  }
+
+  // Posição atual (byte, linha, coluna), para erros no meio de um token
+  fn position(&self) -> (usize, usize, usize) {
+    (self.byte, self.line, self.current - self.line_start + 1)
+  }
+
+  fn span_from(&self, (start, line, column): (usize, usize, usize)) -> Span {
+    Span { start, end: self.byte, line, column }
+  }
+
   fn add_token(&mut self, token_type: TokenType) {
     let text: String = self.source[self.start..self.current]
         .iter()
         .collect();  
     
-    self.tokens.push(Token::new(
-        token_type,
-        text,
-        self.line
-    ));
+    let span = self.span();
+    self.tokens.push(Token::new(token_type, text, span));
   }
 
 }
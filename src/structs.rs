@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::function::Function;
+use crate::object::Object;
+
+// Método definido num `impl`, já com o escopo onde o bloco rodou
+#[derive(Debug, Clone)]
+pub struct StructMethod {
+  pub function: Rc<Function>,
+  pub has_self: bool,
+}
+
+// O tipo criado por `struct User { ... }`. Os métodos chegam depois,
+// quando os blocos `impl` são executados. Os de `impl Trait for User`
+// ficam separados por trait: os do próprio struct têm prioridade, como no Rust.
+#[derive(Debug)]
+pub struct StructDef {
+  pub name: String,
+  pub fields: Vec<String>,
+  methods: RefCell<HashMap<String, StructMethod>>,
+  trait_methods: RefCell<HashMap<String, Vec<(String, StructMethod)>>>,
+}
+
+impl StructDef {
+  pub fn new(name: &str, fields: Vec<String>) -> Self {
+    Self {
+      name: name.to_string(),
+      fields,
+      methods: RefCell::new(HashMap::new()),
+      trait_methods: RefCell::new(HashMap::new()),
+    }
+  }
+
+  pub fn has_field(&self, name: &str) -> bool {
+    self.fields.iter().any(|field| field == name)
+  }
+
+  // `Err` com os nomes dos traits quando mais de um trait define o método
+  pub fn method(&self, name: &str) -> Result<Option<StructMethod>, Vec<String>> {
+    if let Some(method) = self.methods.borrow().get(name) {
+      return Ok(Some(method.clone()));
+    }
+
+    match self.trait_methods.borrow().get(name).map(Vec::as_slice) {
+      None | Some([]) => Ok(None),
+      Some([(_, method)]) => Ok(Some(method.clone())),
+      Some(candidates) => Err(candidates.iter().map(|(trait_name, _)| trait_name.clone()).collect()),
+    }
+  }
+
+  pub fn add_method(&self, name: &str, method: StructMethod) {
+    self.methods.borrow_mut().insert(name.to_string(), method);
+  }
+
+  pub fn add_trait_method(&self, trait_name: &str, name: &str, method: StructMethod) {
+    let mut trait_methods = self.trait_methods.borrow_mut();
+    let candidates = trait_methods.entry(name.to_string()).or_default();
+
+    candidates.retain(|(other, _)| other != trait_name);
+    candidates.push((trait_name.to_string(), method));
+  }
+}
+
+impl fmt::Display for StructDef {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<struct {}>", self.name)
+  }
+}
+
+// Valor criado com `User { ... }`, com os campos na ordem da declaração.
+// Os campos são compartilhados: `self.age += 1` altera quem chamou.
+#[derive(Debug)]
+pub struct Instance {
+  pub def: Rc<StructDef>,
+  pub fields: Rc<RefCell<Object>>,
+}
+
+impl Instance {
+  pub fn new(def: Rc<StructDef>, fields: Object) -> Self {
+    Self { def, fields: Rc::new(RefCell::new(fields)) }
+  }
+}
+
+impl fmt::Display for Instance {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} {}", self.def.name, self.fields.borrow())
+  }
+}
@@ -8,17 +8,23 @@ pub enum TokenType {
     LeftBrace, RightBrace,      // { }
     LeftBracket, RightBracket,  // [ ] 
     Comma, Question,            // , ?
+    QuestionQuestion, QuestionDot, // ?? ?.
     Dot, Range, Spread,         // . .. ...
     Colon, Semicolon,           // :  ;    
 
     // Mathematical and Logical Operators / Operadores Matemáticos e Lógicos
     Minus, Plus, Slash, Star, // -, +, /, *
+    Percent, StarStar,        // % **
+    WrappingMinus, WrappingPlus, WrappingStar,       // -% +% *%
+    SaturatingMinus, SaturatingPlus, SaturatingStar, // -| +| *|
     
     // Comparison and Assignment / Comparação e Atribuição
     Bang, BangEqual,       // ! !=
     Equal, EqualEqual,     // = ==
     Greater, GreaterEqual, // > >=
     Less, LessEqual,       // < <=
+    PlusEqual, MinusEqual, // += -=
+    StarEqual, SlashEqual, // *= /=
     Arrow,                 // =>     
     SimpleArrow,           // ->
     
@@ -31,10 +37,16 @@ pub enum TokenType {
     BitAnd,    // &
     OrLogical, // ||
     OrBitwise, // |
+    BitXor,    // ^
+    BitNot,    // ~
+    ShiftLeft, ShiftRight, // << >>
 
     // Literals / Literais
     Identifier(String), 
     String(String), 
+    // "a ${x} b ${y} c" vira TemplatePart("a "), x, TemplatePart(" b "), y, TemplateEnd(" c")
+    TemplatePart(String),
+    TemplateEnd(String),
     Integer(i64),
     Float(f64),
 
@@ -55,10 +67,10 @@ pub enum TokenType {
     Match,
 
     // Control Structures / Estruturas de Controle
-    If, Else, Return, While, For, Loop,
+    If, Else, Return, While, For, In, Loop, Break, Continue,
 
     // Declarations / Declarações
-    Fn, Let, Const, Struct,
+    Fn, Let, Const, Struct, Impl,
     
     // Const Values / Valores Constantes 
     True, False, Null, 
@@ -72,22 +84,57 @@ pub enum TokenType {
     Eof,
 }
 
-#[allow(dead_code)]
+// Posição de um trecho do código: `start` e `end` são offsets em bytes
+// (end exclusivo), `line` e `column` começam em 1 e apontam para o início.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    // Do início deste trecho até o fim de `other`
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end.max(self.end),
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
-    pub line: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, line: usize) -> Self {
+    pub fn new(token_type: TokenType, lexeme: String, span: Span) -> Self {
         Self {
             token_type,
             lexeme,
-            line,
+            span,
         }
     }
+
+    // `+=` vira `+` (e assim por diante), para reaproveitar a aritmética dos binários
+    pub fn compound_operator(&self) -> Option<Token> {
+        let token_type = match self.token_type {
+            TokenType::PlusEqual => TokenType::Plus,
+            TokenType::MinusEqual => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            _ => return None,
+        };
+
+        let lexeme = self.lexeme.trim_end_matches('=').to_string();
+        Some(Token::new(token_type, lexeme, self.span))
+    }
 }
 
 impl Display for Token {
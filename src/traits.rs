@@ -0,0 +1,32 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::function::Function;
+
+// O tipo criado por `trait Display { ... }`. Os métodos padrão já vêm
+// com o escopo onde o trait foi declarado, como os de um `impl`.
+#[derive(Debug)]
+pub struct TraitDef {
+  pub name: String,
+  pub methods: Vec<TraitMethodDef>,
+}
+
+#[derive(Debug)]
+pub struct TraitMethodDef {
+  pub name: String,
+  pub arity: usize,
+  pub has_self: bool,
+  pub default: Option<Rc<Function>>,
+}
+
+impl TraitDef {
+  pub fn method(&self, name: &str) -> Option<&TraitMethodDef> {
+    self.methods.iter().find(|method| method.name == name)
+  }
+}
+
+impl fmt::Display for TraitDef {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<trait {}>", self.name)
+  }
+}
@@ -0,0 +1,80 @@
+// Cada arquivo de teste usa só parte destas funções
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::rc::Rc;
+
+use shift::{Interpreter, RuntimeValue, ShiftError};
+
+// Roda o script no interpretador e devolve o que ele imprimiu
+pub fn interpret(source: &str) -> Result<String, ShiftError> {
+  let output = Rc::new(RefCell::new(String::new()));
+  let mut interpreter = Interpreter::new();
+
+  let sink = Rc::clone(&output);
+  interpreter.define_native("print", 1, move |args| {
+    sink.borrow_mut().push_str(&format!("{}\n", args[0]));
+    Ok(RuntimeValue::Null)
+  });
+
+  shift::run(&mut interpreter, source)?;
+  Ok(output.take())
+}
+
+pub fn run_error(source: &str) -> ShiftError {
+  match interpret(source) {
+    Ok(output) => panic!("expected an error, the script printed:\n{}", output),
+    Err(error) => error,
+  }
+}
+
+pub fn build_error(source: &str) -> ShiftError {
+  match shift::transpile(source) {
+    Ok(code) => panic!("expected a build error, got:\n{}", code),
+    Err(error) => error,
+  }
+}
+
+// Transpila, compila com o rustc e devolve o que o binário imprimiu
+pub fn compile(name: &str, source: &str) -> String {
+  let run = Command::new(build(name, source)).output().expect("run the compiled script");
+  assert!(run.status.success(), "compiled script failed:\n{}", String::from_utf8_lossy(&run.stderr));
+  String::from_utf8(run.stdout).expect("utf-8 output")
+}
+
+// Para scripts que devem falhar: devolve o stderr do binário
+pub fn compile_failure(name: &str, source: &str) -> String {
+  let run = Command::new(build(name, source)).output().expect("run the compiled script");
+  assert!(!run.status.success(), "expected the compiled script to fail, it printed:\n{}", String::from_utf8_lossy(&run.stdout));
+  String::from_utf8(run.stderr).expect("utf-8 output")
+}
+
+fn build(name: &str, source: &str) -> PathBuf {
+  let code = shift::transpile(source).unwrap_or_else(|error| panic!("{}", error));
+
+  let dir = std::env::temp_dir().join(format!("shift-tests-{}", std::process::id()));
+  fs::create_dir_all(&dir).expect("temp dir");
+  let file = dir.join(format!("{}.rs", name));
+  let binary: PathBuf = dir.join(name);
+  fs::write(&file, &code).expect("write generated code");
+
+  let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+  let build = Command::new(rustc)
+    .args(["--edition", "2024", "-o"])
+    .arg(&binary)
+    .arg(&file)
+    .output()
+    .expect("run rustc");
+  assert!(build.status.success(), "rustc failed:\n{}\n{}", String::from_utf8_lossy(&build.stderr), code);
+  binary
+}
+
+// O interpretador e o binário gerado precisam imprimir a mesma coisa
+pub fn same_output(name: &str, source: &str, expected: &str) {
+  let interpreted = interpret(source).unwrap_or_else(|error| panic!("{}", error));
+  assert_eq!(interpreted, expected, "interpreter output");
+  assert_eq!(compile(name, source), expected, "compiled output");
+}
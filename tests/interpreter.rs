@@ -0,0 +1,355 @@
+mod common;
+
+use common::{interpret, run_error};
+use shift::errors::codes;
+use shift::{RuntimeValue, Stmt};
+
+fn value(source: &str) -> RuntimeValue {
+  shift::run_source(source).unwrap_or_else(|error| panic!("{}", error))
+}
+
+#[test]
+fn operator_precedence() {
+  assert_eq!(value("1 + 2 * 3"), RuntimeValue::Integer(7));
+  assert_eq!(value("(1 + 2) * 3"), RuntimeValue::Integer(9));
+  assert_eq!(value("7 / 2 + 0.5"), RuntimeValue::Float(3.5));
+  assert_eq!(value("1 < 2 && 2 < 3 || false"), RuntimeValue::Boolean(true));
+}
+
+#[test]
+fn modulo_exponent_and_bitwise() {
+  assert_eq!(value("1 + 2 * 3 ** 2"), RuntimeValue::Float(19.0));
+  assert_eq!(value("-2 ** 2"), RuntimeValue::Float(-4.0));
+  assert_eq!(value("2 ** 3 ** 2"), RuntimeValue::Float(512.0));
+  assert_eq!(value("6 & 3 | 8 ^ 1"), RuntimeValue::Integer(11));
+  assert_eq!(value("1 << 4 >> 1"), RuntimeValue::Integer(8));
+  assert_eq!(value("~5"), RuntimeValue::Integer(-6));
+  assert_eq!(value("7 % 3 + 1"), RuntimeValue::Integer(2));
+  assert_eq!(value("-7 % 3"), RuntimeValue::Integer(-1));
+}
+
+#[test]
+fn exponent_is_float() {
+  assert_eq!(value("2 ** -1"), RuntimeValue::Float(0.5));
+  assert_eq!(value("4 ** 0.5"), RuntimeValue::Float(2.0));
+  assert_eq!(value("2 ** 64"), RuntimeValue::Float(18446744073709551616.0));
+}
+
+#[test]
+fn error_messages() {
+  assert_eq!(run_error("1 / 0").message, "Division by zero.");
+  assert_eq!(run_error("1 % 0").message, "Modulo by zero.");
+  assert_eq!(run_error("1 + true").message, "The operator '+' requires two numbers or two strings.");
+  assert_eq!(run_error("1 < \"a\"").message, "Comparison operators require numbers.");
+}
+
+#[test]
+fn integer_overflow() {
+  assert_eq!(run_error("9223372036854775807 + 1").code, codes::INTEGER_OVERFLOW);
+  assert_eq!(run_error("let x = -9223372036854775807 - 1\nprint(-x)").code, codes::INTEGER_OVERFLOW);
+  assert_eq!(run_error("let x = -9223372036854775807 - 1\nprint(x / -1)").code, codes::INTEGER_OVERFLOW);
+  assert_eq!(run_error("let x = 9223372036854775807\nx++").code, codes::INTEGER_OVERFLOW);
+  assert_eq!(run_error("let x = 9223372036854775807\nx += 1").code, codes::INTEGER_OVERFLOW);
+  assert_eq!(value("9223372036854775807 +% 1"), RuntimeValue::Integer(i64::MIN));
+  assert_eq!(value("9223372036854775807 +| 1"), RuntimeValue::Integer(i64::MAX));
+  assert_eq!(run_error("1 / 0").code, codes::DIVISION_BY_ZERO);
+}
+
+#[test]
+fn functions_and_closures() {
+  let source = "
+    fn counter() {
+      let count = 0
+      return () => {
+        count = count + 1
+        return count
+      }
+    }
+    let next = counter()
+    next()
+    print(next())
+    print(len(\"shift\"))
+  ";
+  assert_eq!(interpret(source).unwrap(), "2\n5\n");
+  assert_eq!(run_error("let x = 1\nx()").code, codes::NOT_CALLABLE);
+}
+
+#[test]
+fn top_level_declarations_are_hoisted() {
+  assert_eq!(interpret("print(double(21))\nfn double(x) { return x * 2 }").unwrap(), "42\n");
+
+  let source = "
+    let u = User { n: 2 }
+    print(u.twice())
+    fn double(x) { return x * 2 }
+    impl User { fn twice(self) { return double(self.n) } }
+    struct User { n }
+  ";
+  assert_eq!(interpret(source).unwrap(), "4\n");
+
+  let source = "
+    print(match first() { Light.On => \"on\", Light.Off => \"off\" })
+    fn first() -> Light { return Light.On }
+    enum Light { On, Off }
+  ";
+  assert_eq!(interpret(source).unwrap(), "on\n");
+
+  let source = "
+    print(Dot { x: 1 }.show())
+    impl Show for Dot { }
+    trait Show { fn show(self) -> string { return \"dot\" } }
+    struct Dot { x: int }
+  ";
+  assert_eq!(interpret(source).unwrap(), "dot\n");
+}
+
+#[test]
+fn jumps_outside_of_their_statement() {
+  // O parser recusa, mas uma AST montada à mão chega no interpretador
+  let mut interpreter = shift::Interpreter::new();
+  assert_eq!(interpreter.interpret(&[Stmt::Break]).unwrap_err().code, codes::OUTSIDE_LOOP);
+  assert_eq!(interpreter.interpret(&[Stmt::Continue]).unwrap_err().code, codes::OUTSIDE_LOOP);
+  assert_eq!(interpreter.interpret(&[Stmt::Return { value: None }]).unwrap_err().code, codes::OUTSIDE_FUNCTION);
+}
+
+#[test]
+fn ternary_operator() {
+  assert_eq!(value("1 < 2 ? 10 : 20"), RuntimeValue::Integer(10));
+  assert_eq!(value("false ? 1 : true ? 2 : 3"), RuntimeValue::Integer(2));
+  assert_eq!(interpret("true ? print(\"then\") : print(\"else\")").unwrap(), "then\n");
+}
+
+#[test]
+fn null_coalescing_and_optional_chaining() {
+  let source = "
+    struct User { name: string, friend: User? }
+    fn loud(x: int) -> int {
+      print(\"evaluated\")
+      return x
+    }
+    let a: int? = null
+    let o = null
+    print(5 ?? loud(1))
+    print(a ?? loud(2))
+    print(o?.name)
+    print(o?.name ?? \"missing\")
+    print(o?.greet(loud(3)))
+    let u = User { name: \"ana\", friend: null }
+    print(u.friend?.name)
+    print(u.friend?.name ?? \"alone\")
+    print(u.friend?.greet())
+  ";
+  assert_eq!(interpret(source).unwrap(), "5\nevaluated\n2\nnull\nmissing\nnull\nnull\nalone\nnull\n");
+  assert_eq!(run_error("let u = null\nprint(u.name)").code, codes::TYPE_MISMATCH);
+}
+
+#[test]
+fn optional_chains_stop_at_the_first_null() {
+  let source = "
+    let o = null
+    print(o?.a.b)
+    print(o?.a.b())
+    print(o?.a().b.c)
+    print(o?.a[0].c)
+    let p = { a: { b: [1, 2] } }
+    print(p?.a.b[1])
+  ";
+  assert_eq!(interpret(source).unwrap(), "null\nnull\nnull\nnull\n2\n");
+
+  // Só o `?.` para a cadeia: um null no meio ainda é erro
+  assert_eq!(run_error("let o = { a: null }\nprint(o?.a.b)").code, codes::TYPE_MISMATCH);
+}
+
+#[test]
+fn assignment_places() {
+  let source = "
+    let x = 1
+    x++
+    ++x
+    x += 10
+    let a = [1, 2, 3]
+    a[0] += 5
+    a[1]++
+    a[-1] = 9
+    print(x)
+    print(a)
+    print([0, ...a, 10])
+  ";
+  assert_eq!(interpret(source).unwrap(), "13\n[6, 3, 9]\n[0, 6, 3, 9, 10]\n");
+  assert_eq!(run_error("const c = 1\nc += 1").code, codes::CONSTANT_ASSIGNMENT);
+  assert_eq!(run_error("let a = [1]\na[3] = 0").code, codes::INDEX_OUT_OF_BOUNDS);
+}
+
+#[test]
+fn printing_cycles() {
+  let source = "
+    let a = [1]
+    a[0] = a
+    print(a)
+    print([a, a])
+    let o = { x: 1 }
+    o.me = o
+    print(o)
+    struct Node { next: int }
+    let n = Node { next: 0 }
+    n.next = n
+    print(n)
+  ";
+  assert_eq!(interpret(source).unwrap(), "[[...]]\n[[[...]], [[...]]]\n{ x: 1, me: {...} }\nNode { next: Node {...} }\n");
+}
+
+#[test]
+fn array_shrunk_before_the_write() {
+  let mut interpreter = shift::Interpreter::new();
+  interpreter.define_native("clear", 1, |args| match &args[0] {
+    RuntimeValue::Array(items) => {
+      items.borrow_mut().clear();
+      Ok(RuntimeValue::Null)
+    },
+    _ => Err("expected an array".to_string()),
+  });
+
+  let error = shift::run(&mut interpreter, "let a = [1, 2, 3]\na[2] = clear(a)").unwrap_err();
+  assert_eq!(error.code, codes::INDEX_OUT_OF_BOUNDS);
+}
+
+#[test]
+fn objects() {
+  let source = "
+    let name = \"ana\"
+    let user = { name, age: 30 }
+    user.city = \"rio\"
+    user[\"age\"] += 1
+    print(user)
+    print(user.age)
+    print(user.missing ?? \"none\")
+    let copy = { ...user, age: 0 }
+    print(copy)
+    let alias = user
+    alias.name = \"bia\"
+    print(user.name)
+  ";
+  let expected = "{ name: \"ana\", age: 31, city: \"rio\" }\n31\nnone\n{ name: \"ana\", age: 0, city: \"rio\" }\nbia\n";
+  assert_eq!(interpret(source).unwrap(), expected);
+  assert_eq!(run_error("let o = { a: 1 }\nprint(o[0])").code, codes::TYPE_MISMATCH);
+  assert_eq!(run_error("let o = { ...[1] }").code, codes::TYPE_MISMATCH);
+}
+
+#[test]
+fn structs() {
+  let source = "
+    struct User { name: string, age: int }
+    impl User {
+      fn new(name: string) -> User { return User { name, age: 0 } }
+      fn birthday(self) { self.age += 1 }
+      fn greet(self) -> string { return \"hi \" + self.name }
+    }
+    let u = User.new(\"ana\")
+    u.birthday()
+    let alias = u
+    alias.birthday()
+    print(u.age)
+    print(u.greet())
+    let older = User { ...u, age: 40 }
+    print(older)
+    print(u == alias)
+    print(u == older)
+  ";
+  let expected = "2\nhi ana\nUser { name: \"ana\", age: 40 }\ntrue\nfalse\n";
+  assert_eq!(interpret(source).unwrap(), expected);
+
+  let prelude = "struct P { x: int }\nimpl P { fn make() -> P { return P { x: 0 } } }\n";
+  assert_eq!(run_error(&format!("{}P {{ x: 1, y: 2 }}", prelude)).code, codes::UNDEFINED_PROPERTY);
+  assert_eq!(run_error(&format!("{}P {{}}", prelude)).code, codes::UNDEFINED_PROPERTY);
+  assert_eq!(run_error(&format!("{}let p = P {{ x: 1 }}\nprint(p.y)", prelude)).code, codes::UNDEFINED_PROPERTY);
+  assert_eq!(run_error(&format!("{}let p = P {{ x: 1 }}\np.make()", prelude)).code, codes::UNDEFINED_PROPERTY);
+}
+
+#[test]
+fn match_patterns() {
+  let source = "
+    enum Shape { Circle(float), Rect { w: float, h: float }, Empty }
+    fn area(s: Shape) -> float {
+      return match s {
+        Shape.Circle(r) => 3.0 * r * r,
+        Shape.Rect { w, h } => w * h,
+        Shape.Empty => 0.0,
+      }
+    }
+    print(area(Shape.Circle(2.0)))
+    print(area(Shape.Rect { w: 2.0, h: 3.5 }))
+    print(area(Shape.Empty))
+    print(match 5 { 0 => \"zero\", 1..10 => \"small\", _ => \"big\" })
+  ";
+  assert_eq!(interpret(source).unwrap(), "12\n7\n0\nsmall\n");
+  assert_eq!(run_error("match 3 { 1 => 1 }").code, codes::NO_MATCH);
+}
+
+#[test]
+fn match_guards() {
+  let source = "
+    let flag = true
+    let limit = 2
+    print(match 3 { x if flag => 1, _ => 0 })
+    print(match 3 { x if (x > 5) => 1, _ => 0 })
+    print(match 3 { n if n > limit => \"big\", _ => \"small\" })
+    fn check(f) -> bool { return f(3) }
+    print(match 3 { n if check(y => y == n) => \"yes\", _ => \"no\" })
+  ";
+  assert_eq!(interpret(source).unwrap(), "1\n0\nbig\nyes\n");
+}
+
+#[test]
+fn traits_dispatch() {
+  let source = "
+    trait Show {
+      fn show(self) -> string
+      fn wrapped(self) -> string { return \"<\" + self.show() + \">\" }
+    }
+    struct A { x: int }
+    struct B { y: int }
+    impl Show for A { fn show(self) -> string { return \"a\" } }
+    impl Show for B {
+      fn show(self) -> string { return \"b\" }
+      fn wrapped(self) -> string { return \"[b]\" }
+    }
+    let items = [A { x: 1 }, B { y: 2 }]
+    for i in 0..2 { print(items[i].wrapped()) }
+  ";
+  assert_eq!(interpret(source).unwrap(), "<a>\n[b]\n");
+
+  let missing = "trait Show { fn show(self) -> string }\nstruct A { x: int }\nimpl Show for A { }";
+  assert_eq!(run_error(missing).code, codes::TRAIT_MISMATCH);
+}
+
+#[test]
+fn inherent_methods_win_over_trait_defaults() {
+  let header = "
+    trait Show { fn show(self) -> string { return \"default\" } }
+    struct U { x: int }
+  ";
+  let inherent = "impl U { fn show(self) -> string { return \"inherent\" } }\n";
+  let implementation = "impl Show for U { }\n";
+
+  // A ordem dos blocos `impl` não muda o resultado
+  for blocks in [format!("{}{}", inherent, implementation), format!("{}{}", implementation, inherent)] {
+    let source = format!("{}{}print(U {{ x: 1 }}.show())", header, blocks);
+    assert_eq!(interpret(&source).unwrap(), "inherent\n");
+  }
+}
+
+#[test]
+fn same_method_in_two_traits() {
+  let source = "
+    trait A { fn name(self) -> string { return \"a\" } }
+    trait B { fn name(self) -> string { return \"b\" } }
+    struct U { x: int }
+    impl A for U { }
+    impl B for U { }
+  ";
+  let error = run_error(&format!("{}print(U {{ x: 1 }}.name())", source));
+  assert_eq!(error.code, codes::TRAIT_MISMATCH);
+
+  let resolved = format!("{}impl U {{ fn name(self) -> string {{ return \"u\" }} }}\nprint(U {{ x: 1 }}.name())", source);
+  assert_eq!(interpret(&resolved).unwrap(), "u\n");
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{interpret, run_error};
+use shift::errors::codes;
+
+#[test]
+fn struct_literals_in_conditions() {
+  let source = "
+    struct P { x: int }
+    fn f(p: P) -> int { return p.x }
+    if f(P { x: 1 }) == 1 { print(\"call\") }
+    if [P { x: 2 }][0].x == 2 { print(\"array\") }
+    fn call(g) { return g() }
+    if call(() => { return P { x: 3 } }).x == 3 { print(\"closure\") }
+  ";
+  assert_eq!(interpret(source).unwrap(), "call\narray\nclosure\n");
+
+  // Sem parênteses o `{` ainda abre o bloco do if
+  assert_eq!(run_error("struct P { x: int }\nlet P = 1\nif P { x: 1 } == 1 {}").code, codes::EXPECTED_EXPRESSION);
+}
+
+#[test]
+fn bare_return_ends_at_the_line() {
+  let source = "
+    fn g() {
+      return
+      print(\"unreachable\")
+    }
+    print(g())
+  ";
+  assert_eq!(interpret(source).unwrap(), "null\n");
+}
+
+#[test]
+fn non_exhaustive_matches_warn() {
+  let statements = shift::parse("
+    enum Light { On, Off, Blink }
+    let l = Light.On
+    print(match l { Light.On => 1, Light.Off => 0 })
+    print(match l { Light.On => 1, _ => 0 })
+  ").unwrap();
+
+  let warnings = shift::lint::check(&statements);
+  assert_eq!(warnings.len(), 1);
+  assert_eq!(warnings[0].code, codes::NON_EXHAUSTIVE_MATCH);
+  assert!(warnings[0].message.contains("Blink"), "{}", warnings[0].message);
+}
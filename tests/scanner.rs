@@ -0,0 +1,36 @@
+mod common;
+
+use common::{interpret, run_error};
+use shift::errors::codes;
+use shift::RuntimeValue;
+
+#[test]
+fn number_literals() {
+  assert_eq!(shift::run_source("0xff + 0b101 + 0o17").unwrap(), RuntimeValue::Integer(275));
+  assert_eq!(shift::run_source("1_000_000").unwrap(), RuntimeValue::Integer(1_000_000));
+  assert_eq!(shift::run_source("1.5e3").unwrap(), RuntimeValue::Float(1500.0));
+  assert_eq!(shift::run_source("2e-2").unwrap(), RuntimeValue::Float(0.02));
+}
+
+#[test]
+fn invalid_numbers() {
+  assert_eq!(run_error("0x").code, codes::INVALID_NUMBER);
+  assert_eq!(run_error("99999999999999999999").code, codes::NUMBER_OUT_OF_RANGE);
+}
+
+#[test]
+fn string_escapes() {
+  assert_eq!(interpret(r#"print("a\tb\n\"q\" \\ \u{41}")"#).unwrap(), "a\tb\n\"q\" \\ A\n");
+  assert_eq!(run_error(r#"print("\q")"#).code, codes::INVALID_ESCAPE);
+}
+
+#[test]
+fn unterminated_input() {
+  assert_eq!(run_error("/* nunca fecha").code, codes::UNTERMINATED_COMMENT);
+  assert_eq!(run_error("print(\"abc)").code, codes::UNTERMINATED_STRING);
+}
+
+#[test]
+fn interpolation() {
+  assert_eq!(interpret("let n = 2\nprint(\"n = ${n * 2}!\")").unwrap(), "n = 4!\n");
+}
@@ -0,0 +1,456 @@
+mod common;
+
+use common::{build_error, compile_failure, run_error, same_output};
+use shift::errors::codes;
+
+#[test]
+fn arithmetic_and_functions() {
+  let source = "
+    print(1 + 2 * 3)
+    print(7.0 / 2)
+    let total = 0
+    for i in 0..4 { total = total + i }
+    print(total)
+    print(double(21))
+    fn double(x: int) -> int { return x * 2 }
+  ";
+  same_output("arithmetic", source, "7\n3.5\n6\n42\n");
+}
+
+#[test]
+fn missing_return_type() {
+  assert_eq!(build_error("fn f() { return 1 }").code, codes::MISSING_ANNOTATION);
+}
+
+#[test]
+fn string_escapes() {
+  same_output("escapes", r#"print("a\tb \"q\" \\ \u{41}")"#, "a\tb \"q\" \\ A\n");
+}
+
+#[test]
+fn number_literals() {
+  same_output("numbers", "print(0xff + 0b101 + 0o17 + 1_000)\nprint(1.5e3)", "1275\n1500\n");
+}
+
+#[test]
+fn integer_overflow_fails_like_the_interpreter() {
+  let cases = [
+    ("overflow_add", "let x = 9223372036854775807\nprint(x + 1)", "Integer overflow in '+'."),
+    ("overflow_div", "let x = -9223372036854775807 - 1\nprint(x / -1)", "Integer overflow in '/'."),
+    ("overflow_neg", "let x = -9223372036854775807 - 1\nprint(-x)", "Integer overflow in '-'."),
+    ("division_by_zero", "let zero = 0\nprint(5 / zero)", "Division by zero."),
+    ("modulo_by_zero", "let zero = 0\nprint(5 % zero)", "Modulo by zero."),
+    ("overflow_update", "let x = 9223372036854775807\nx++", "Integer overflow in '++'."),
+    ("overflow_compound", "let x = 9223372036854775807\nx += 1", "Integer overflow in '+'."),
+  ];
+
+  for (name, source, message) in cases {
+    assert_eq!(run_error(source).message, message, "{}", source);
+    assert!(compile_failure(name, source).contains(message), "{}", source);
+  }
+  same_output("wrapping", "print(9223372036854775807 +% 1)\nprint(9223372036854775807 +| 1)", "-9223372036854775808\n9223372036854775807\n");
+}
+
+#[test]
+fn modulo_exponent_and_bitwise() {
+  same_output(
+    "operators",
+    "print(1 + 2 * 3 ** 2)\nprint(6 & 3 | 8 ^ 1)\nprint(1 << 4 >> 1)\nprint(~5)\nprint(-7 % 3)\nprint(7.5 % 2)",
+    "19\n11\n8\n-6\n-1\n1.5\n",
+  );
+}
+
+#[test]
+fn exponent_is_float() {
+  same_output(
+    "exponent",
+    "let base = 2\nlet power = -1\nprint(base ** power)\nprint(2 ** 64)\nprint(4 ** 0.5)",
+    "0.5\n18446744073709552000\n2\n",
+  );
+}
+
+#[test]
+fn increment_and_decrement() {
+  same_output(
+    "update",
+    "let x = 1\nprint(x++)\nprint(++x)\nprint(x--)\nlet f = 0.5\nf++\nprint(f)",
+    "1\n3\n3\n1.5\n",
+  );
+}
+
+#[test]
+fn compound_assignment() {
+  same_output(
+    "compound",
+    "let x = 10\nx += 5\nx -= 3\nx *= 2\nx /= 4\nprint(x)\nlet f = 1.5\nf += 1\nprint(f)\nlet s = \"a\"\ns += \"b\"\nprint(s)",
+    "6\n2.5\nab\n",
+  );
+}
+
+#[test]
+fn ternary_operator() {
+  let source = "
+    let x = 3
+    print(x > 2 ? \"big\" : \"small\")
+    print(x > 5 ? 1 : 2.5)
+    print(false ? 1 : true ? 2 : 3)
+  ";
+  same_output("ternary", source, "big\n2.5\n2\n");
+}
+
+#[test]
+fn null_coalescing() {
+  let source = "
+    fn find(name: string) -> int? {
+      if name == \"ana\" { return 3 }
+      return null
+    }
+    fn loud(x: int) -> int {
+      print(\"evaluated\")
+      return x
+    }
+    let a: int? = null
+    let b: int? = 5
+    print(b ?? loud(2))
+    print(a ?? loud(3))
+    print(a ?? b ?? 7)
+    print(find(\"ana\"))
+    print(find(\"bob\") ?? 0)
+    let name: string? = null
+    let copy = name
+    let pick = b == 5 ? 1 : null
+    print(\"${a == null} ${b == 5} ${a} ${copy} ${pick}\")
+  ";
+  same_output("nullable", source, "5\nevaluated\n3\n5\n3\n0\ntrue true null null 1\n");
+}
+
+#[test]
+fn nullable_operators_need_nullable_types() {
+  assert_eq!(build_error("let x = 5\nprint(x ?? 1)").code, codes::TYPE_ERROR);
+  assert_eq!(build_error("let x = 5\nprint(x == null)").code, codes::TYPE_ERROR);
+  assert_eq!(build_error("struct U { a: int }\nlet u = U { a: 1 }\nprint(u?.a)").code, codes::TYPE_ERROR);
+  assert_eq!(build_error("let n = null").code, codes::MISSING_ANNOTATION);
+}
+
+#[test]
+fn assignment_places() {
+  same_output(
+    "places",
+    "let x = 1\nx++\nx += 10\nlet a = [1, 2, 3]\na[0] += 5\na[1]++\nprint(x)\nprint(a)",
+    "12\n[6, 3, 3]\n",
+  );
+}
+
+#[test]
+fn arrays_and_spread() {
+  let source = "
+    let a = [1, 2, 3]
+    a[-1] = 9
+    print([0, ...a, 10])
+    print(a[-2])
+    let grid = [[1, 2], [3, 4]]
+    grid[1][0] = 7
+    print(grid)
+    let maybe: int?[] = [1, null]
+    print(maybe[1] ?? 5)
+  ";
+  same_output("arrays", source, "[0, 1, 2, 9, 10]\n2\n[[1, 2], [7, 4]]\n5\n");
+  assert!(compile_failure("array_bounds", "let a = [1]\nprint(a[3])").contains("Index 3 is out of bounds for an array of length 1."));
+}
+
+#[test]
+fn arrays_changed_through_a_copy_are_rejected() {
+  let sources = [
+    "let a = [1, 2, 3]\nlet b = a\nb[0] = 99\nprint(a[0])",
+    "let a = [1, 2, 3]\nlet b = a\na[0] = 99\nprint(b[0])",
+    "fn bump(l: int[]) { l[0] = 42 }\nlet a = [1, 2, 3]\nbump(a)\nprint(a[0])",
+    "fn keep(l: int[]) -> int[] { return l }\nlet a = [1]\nlet b = keep(a)\nb[0] = 2\nprint(a)",
+    "let grid = [[1, 2]]\nlet row = grid[0]\nrow[0] = 9\nprint(grid)",
+    "let row = [1, 2]\nlet grid = [row]\ngrid[0][0] = 9\nprint(row)",
+  ];
+
+  for source in sources {
+    assert_eq!(build_error(source).code, codes::UNSUPPORTED, "{}", source);
+  }
+}
+
+#[test]
+fn arrays_copied_without_sharing_changes() {
+  let source = "
+    fn total(l: int[]) -> int {
+      let t = 0
+      for i in 0..len(l) { t += l[i] }
+      return t
+    }
+    fn make(n: int) -> int[] {
+      let out = [0, 0]
+      out[0] = n
+      return out
+    }
+    let a = [1, 2, 3]
+    a[1] = 10
+    print(total(a))
+    let m = make(4)
+    m[1] = 1
+    print(m)
+    let row = [5, 6]
+    let copy = [...row]
+    copy[0] = 0
+    print(row)
+  ";
+  same_output("array_copies", source, "14\n[4, 1]\n[5, 6]\n");
+}
+
+#[test]
+fn objects_are_not_transpiled() {
+  assert_eq!(build_error("let o = { a: 1 }").code, codes::UNSUPPORTED);
+}
+
+#[test]
+fn structs_and_methods() {
+  let source = "
+    struct Point { x: float, y: float }
+    struct User { name: string, age: int, home: Point }
+    impl User {
+      fn new(name: string) -> User { return User { name: name, age: 0, home: Point { x: 0.5, y: 1.5 } } }
+      fn birthday(self) { self.age += 1 }
+      fn greet(self) -> string { return \"hi \" + self.name }
+    }
+    let u = User.new(\"ana\")
+    u.birthday()
+    u.home.x = 2
+    print(u.greet())
+    print(u.age)
+    let older = User { ...User.new(\"bia\"), age: 40 }
+    print(older)
+  ";
+  let expected = "hi ana\n1\nUser { name: \"bia\", age: 40, home: Point { x: 0.5, y: 1.5 } }\n";
+  same_output("structs", source, expected);
+
+  assert_eq!(build_error("struct P { x }").code, codes::MISSING_ANNOTATION);
+  assert_eq!(build_error("struct P { x: int }\nlet a = P { x: 1 }\nprint(a == a)").code, codes::UNSUPPORTED);
+  assert_eq!(build_error("struct P { x: int }\nlet a = P { x: 1, y: 2 }").code, codes::TYPE_ERROR);
+}
+
+#[test]
+fn top_level_declarations_are_hoisted() {
+  let source = "
+    print(double(21))
+    let u = User { n: 2 }
+    print(u.twice())
+    fn double(x: int) -> int { return x * 2 }
+    impl User { fn twice(self) -> int { return double(self.n) } }
+    struct User { n: int }
+  ";
+  same_output("hoisting", source, "42\n4\n");
+
+  let source = "
+    print(match first() { Light.On => \"on\", Light.Off => \"off\" })
+    fn first() -> Light { return Light.On }
+    enum Light { On, Off }
+  ";
+  same_output("hoisting_enums", source, "on\n");
+
+  let source = "
+    print(Dot { x: 1 }.show())
+    impl Show for Dot { }
+    trait Show { fn show(self) -> string { return \"dot\" } }
+    struct Dot { x: int }
+  ";
+  same_output("hoisting_traits", source, "dot\n");
+}
+
+#[test]
+fn structs_changed_through_a_copy_are_rejected() {
+  let prelude = "
+    struct P { x: int }
+    struct Line { a: P }
+    impl P { fn grow(self) { self.x += 1 } }
+    impl Line { fn start(self) -> P { return self.a } }
+  ";
+  let programs = [
+    "let p = P { x: 1 }\nlet q = p\nq.x = 5\nprint(p.x)",
+    "let p = P { x: 1 }\nlet q = p\np.grow()\nprint(q.x)",
+    "fn reset(p: P) { p.x = 0 }\nlet p = P { x: 1 }\nreset(p)\nprint(p.x)",
+    "let p = P { x: 1 }\nlet line = Line { a: p }\nline.a.x = 7\nprint(p.x)",
+    "let line = Line { a: P { x: 1 } }\nlet p = line.start()\np.x = 7\nprint(line.a.x)",
+  ];
+
+  for program in programs {
+    let source = format!("{}\n{}", prelude, program);
+    assert_eq!(build_error(&source).code, codes::UNSUPPORTED, "{}", program);
+  }
+}
+
+#[test]
+fn structs_copied_without_sharing_changes() {
+  let source = "
+    struct User { name: string, age: int }
+    impl User {
+      fn new(name: string) -> User { return User { name: name, age: 0 } }
+      fn birthday(self) { self.age += 1 }
+    }
+    fn show(u: User) { print(u.name + \" \" + \"${u.age}\") }
+    let u = User.new(\"ana\")
+    u.birthday()
+    show(u)
+    let other = User.new(\"bia\")
+    let copy = other
+    print(copy.name)
+    struct Bag { items: int[] }
+    let bag = Bag { items: [1, 2] }
+    print([...bag.items, 3])
+    print(bag.items)
+  ";
+  same_output("struct_copies", source, "ana 1\nbia\n[1, 2, 3]\n[1, 2]\n");
+}
+
+#[test]
+fn optional_chaining() {
+  let source = "
+    struct User { name: string, nick: string?, age: int }
+    impl User {
+      fn greet(self) -> string { return \"hi \" + self.name }
+      fn birthday(self) { self.age += 1 }
+    }
+    fn find(name: string) -> User? {
+      if name == \"ana\" { return User { name: \"ana\", nick: null, age: 3 } }
+      return null
+    }
+    let u = find(\"ana\")
+    let nobody = find(\"bob\")
+    print(u?.name)
+    print(nobody?.name)
+    print(u?.nick ?? \"no nick\")
+    print(nobody?.greet() ?? \"silence\")
+    u?.birthday()
+    print(u?.age)
+  ";
+  same_output("optional_chaining", source, "ana\nnull\nno nick\nsilence\n4\n");
+}
+
+#[test]
+fn optional_chains_stop_at_the_first_null() {
+  let source = "
+    struct Address { city: string, zips: int[] }
+    struct User { name: string, address: Address, office: Address? }
+    impl User {
+      fn home(self) -> Address { return self.address }
+    }
+    let nobody: User? = null
+    let u: User? = User { name: \"ana\", address: Address { city: \"rio\", zips: [20, 21] }, office: null }
+    print(nobody?.address.city)
+    print(u?.address.city)
+    print(u?.home().city)
+    print(nobody?.address.zips[1])
+    print(u?.address.zips[1])
+    print(u?.office?.city ?? \"no office\")
+  ";
+  same_output("optional_chains", source, "null\nrio\nrio\nnull\n21\nno office\n");
+
+  // `u?.office` pode ser null sem o `?.` ter parado; o interpretador daria erro
+  let source = "
+    struct Address { city: string }
+    struct User { name: string, office: Address? }
+    let u: User? = User { name: \"ana\", office: null }
+    print(u?.office.city)
+  ";
+  assert_eq!(run_error(source).code, codes::TYPE_MISMATCH);
+  assert_eq!(build_error(source).code, codes::TYPE_ERROR);
+}
+
+#[test]
+fn match_patterns() {
+  let source = "
+    enum Shape { Circle(float), Rect { w: float, h: float }, Empty }
+    fn area(s: Shape) -> float {
+      return match s {
+        Shape.Circle(r) => 3.0 * r * r,
+        Shape.Rect { w, h } => w * h,
+        Shape.Empty => 0.0,
+      }
+    }
+    fn size(n: int) -> string {
+      return match n { 0 => \"zero\", 1..10 => \"small\", _ => \"big\" }
+    }
+    print(area(Shape.Circle(2.0)))
+    print(area(Shape.Rect { w: 2.0, h: 3.5 }))
+    print(size(5))
+    print(size(50))
+  ";
+  same_output("match_patterns", source, "12\n7\nsmall\nbig\n");
+}
+
+#[test]
+fn match_guards() {
+  let source = "
+    fn size(n: int, limit: int) -> string {
+      return match n { x if x > limit => \"big\", x if (x == limit) => \"limit\", _ => \"small\" }
+    }
+    let flag = false
+    print(size(3, 2))
+    print(size(2, 2))
+    print(match 1 { x if flag => \"flag\", _ => \"none\" })
+  ";
+  same_output("match_guards", source, "big\nlimit\nnone\n");
+}
+
+#[test]
+fn traits_dispatch() {
+  let source = "
+    trait Show {
+      fn show(self) -> string
+      fn wrapped(self) -> string { return \"<\" + self.show() + \">\" }
+    }
+    struct A { x: int }
+    struct B { y: int }
+    impl Show for A { fn show(self) -> string { return \"a\" } }
+    impl Show for B {
+      fn show(self) -> string { return \"b\" }
+      fn wrapped(self) -> string { return \"[b]\" }
+    }
+    let items: Show[] = [A { x: 1 }, B { y: 2 }]
+    for i in 0..2 { print(items[i].wrapped()) }
+  ";
+  same_output("traits_dispatch", source, "<a>\n[b]\n");
+
+  let wrong = "trait Show { fn show(self) -> string }\nstruct A { x: int }\nimpl Show for A { fn show(self) -> int { return 1 } }";
+  assert_eq!(build_error(wrong).code, codes::TRAIT_MISMATCH);
+}
+
+#[test]
+fn inherent_methods_win_over_trait_defaults() {
+  let source = "
+    trait Show { fn show(self) -> string { return \"default\" } }
+    struct U { x: int }
+    impl Show for U { }
+    impl U { fn show(self) -> string { return \"inherent\" } }
+    print(U { x: 1 }.show())
+  ";
+  same_output("inherent_methods", source, "inherent\n");
+}
+
+#[test]
+fn same_method_in_two_traits() {
+  let source = "
+    trait A { fn name(self) -> string { return \"a\" } }
+    trait B { fn name(self) -> string { return \"b\" } }
+    struct U { x: int }
+    impl A for U { }
+    impl B for U { }
+    print(U { x: 1 }.name())
+  ";
+  assert_eq!(build_error(source).code, codes::TRAIT_MISMATCH);
+}
+
+#[test]
+fn trait_values_cannot_change_self() {
+  let source = "
+    trait Counter { fn bump(self) }
+    struct C { n: int }
+    impl Counter for C { fn bump(self) { self.n += 1 } }
+    let items: Counter[] = [C { n: 0 }]
+    items[0].bump()
+  ";
+  assert_eq!(build_error(source).code, codes::UNSUPPORTED);
+}